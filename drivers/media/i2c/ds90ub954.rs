@@ -16,8 +16,14 @@
  */
 
 use kernel::{
-    arrayvec::ArrayVec, c_str, fwnode, gpio::consumer as gpio, i2c, of, prelude::*, regmap,
+    arrayvec::ArrayVec,
+    c_str, fwnode,
+    gpio::consumer as gpio,
+    i2c, of,
+    prelude::*,
+    regmap,
     str::BStr,
+    sync::{new_mutex, Arc, Mutex},
 };
 
 ///  Deserializer registers
@@ -32,7 +38,7 @@ mod ti954 {
     pub(crate) const DIGITAL_RESET1: usize = 1;
     pub(crate) const RESTART_AUTOLOAD: usize = 2;
 
-    pub(crate) const REG_GENERAL_CFG: usize = 0x2;
+    pub(crate) const REG_GENERAL_CFG: u32 = 0x2;
     pub(crate) const FORCE_REFCLK_DET: usize = 0;
     pub(crate) const RX_PARITY_CHECKER_ENABLE: usize = 1;
     pub(crate) const OUTPUT_SLEEP_STATE_SELECT: usize = 2;
@@ -56,7 +62,7 @@ mod ti954 {
     pub(crate) const REG_PAR_ERR_THOLD_LO: usize = 0x6;
     pub(crate) const PAR_ERR_THOLD_LO: usize = 0;
 
-    pub(crate) const REG_BCC_WD_CTL: usize = 0x07;
+    pub(crate) const REG_BCC_WD_CTL: u32 = 0x07;
     pub(crate) const BCC_WATCHDOG_TIMER_DISABLE: usize = 0;
     pub(crate) const BCC_WATCHDOG_TIMER: usize = 1;
 
@@ -184,7 +190,7 @@ mod ti954 {
     pub(crate) const FWD_PORT0_DIS: u32 = 4;
     pub(crate) const FWD_PORT1_DIS: usize = 6;
 
-    pub(crate) const FWD_CTL2: usize = 0x21;
+    pub(crate) const REG_FWD_CTL2: u32 = 0x21;
     pub(crate) const CSI0_RR_RWD: usize = 0;
     pub(crate) const CSI0_SYNC_FWD: usize = 2;
     pub(crate) const FWD_SYNC_AS_AVAIL: usize = 6;
@@ -249,13 +255,13 @@ mod ti954 {
     pub(crate) const CSI_2_LANE: u32 = 2;
     pub(crate) const CSI_1_LANE: u32 = 3;
 
-    pub(crate) const REG_CSI_CTL2: usize = 0x34;
+    pub(crate) const REG_CSI_CTL2: u32 = 0x34;
     pub(crate) const CSI_CAL_PERIODIC: usize = 0;
     pub(crate) const CSI_CAL_SINGLE: usize = 1;
     pub(crate) const CSI_CAL_INV: usize = 2;
     pub(crate) const CSI_PASS_MODE: usize = 3;
 
-    pub(crate) const REG_CSI_STS: usize = 0x35;
+    pub(crate) const REG_CSI_STS: u32 = 0x35;
     pub(crate) const TX_PORT_PASS: usize = 0;
     pub(crate) const TX_PORT_SYNC: usize = 1;
 
@@ -265,7 +271,7 @@ mod ti954 {
     pub(crate) const IE_CSI_SYNC: usize = 2;
     pub(crate) const IE_CSI_SYNC_ERROR: usize = 3;
 
-    pub(crate) const REG_CSI_TX_ISR: usize = 0x37;
+    pub(crate) const REG_CSI_TX_ISR: u32 = 0x37;
     pub(crate) const IS_CSI_PASS: usize = 0;
     pub(crate) const IS_CSI_PASS_ERR_OR: usize = 1;
     pub(crate) const IS_CSI_SYNC: usize = 2;
@@ -296,7 +302,7 @@ mod ti954 {
     pub(crate) const REG_FPD3_CAP: usize = 0x4a;
     pub(crate) const FPD3_ENC_CRC_CAP: usize = 4;
 
-    pub(crate) const REG_RAQ_EMBED_DTYPE: usize = 0x4b;
+    pub(crate) const REG_RAQ_EMBED_DTYPE: u32 = 0x4b;
     pub(crate) const EMBED_DTYPE_ID: usize = 0;
     pub(crate) const EMBED_DTYPE_EN: usize = 6;
 
@@ -306,7 +312,7 @@ mod ti954 {
     pub(crate) const RX_READ_PORT: usize = 4;
     pub(crate) const PHYS_PORT_NUM: usize = 6;
 
-    pub(crate) const REG_RX_PORT_STS1: usize = 0x4d;
+    pub(crate) const REG_RX_PORT_STS1: u32 = 0x4d;
     pub(crate) const LOCK_STS: usize = 0;
     pub(crate) const PORT_PASS: usize = 1;
     pub(crate) const PARITY_ERROR: usize = 2;
@@ -423,7 +429,7 @@ mod ti954 {
     pub(crate) const REG_ALIAS_ID7: usize = 0x6c;
     pub(crate) const ALIAS_ID7: usize = 1;
 
-    pub(crate) const REG_PORT_CONFIG: usize = 0x6d;
+    pub(crate) const REG_PORT_CONFIG: u32 = 0x6d;
     pub(crate) const FPD3_MODE: usize = 0;
     pub(crate) const COAX_MODE: usize = 2;
     pub(crate) const CSI_FWD_LEN: usize = 3;
@@ -468,10 +474,10 @@ mod ti954 {
     pub(crate) const FREQ_STABLE_THR: usize = 4;
     pub(crate) const FREQ_HYST: usize = 6;
 
-    pub(crate) const REG_MAILBOX_1: usize = 0x78;
+    pub(crate) const REG_MAILBOX_1: u32 = 0x78;
     pub(crate) const MAILBOX_0: usize = 0;
 
-    pub(crate) const REG_MAILBOX_2: usize = 0x79;
+    pub(crate) const REG_MAILBOX_2: u32 = 0x79;
     pub(crate) const MAILBOX_1: usize = 0;
 
     pub(crate) const REG_CSI_RX_STS: usize = 0x7a;
@@ -491,7 +497,7 @@ mod ti954 {
     pub(crate) const DISCARD_ON_PAR_ERR: usize = 5;
     pub(crate) const RAW10_8BIT_CTL: usize = 6;
 
-    pub(crate) const REG_PORT_PASS_CTL: usize = 0x7d;
+    pub(crate) const REG_PORT_PASS_CTL: u32 = 0x7d;
     pub(crate) const PASS_THRESHOLD: usize = 0;
     pub(crate) const PASS_WDOG_DIS: usize = 2;
     pub(crate) const PASS_PARITY_ERR: usize = 3;
@@ -505,18 +511,18 @@ mod ti954 {
     pub(crate) const REG_SEN_INT_FALL_CTL: usize = 0x7f;
     pub(crate) const SEN_INT_FALL_MASK: usize = 0;
 
-    pub(crate) const REG_REFCLK_FREQ: usize = 0xa5;
+    pub(crate) const REG_REFCLK_FREQ: u32 = 0xa5;
     pub(crate) const REFCLK_FREQ: usize = 0;
 
-    pub(crate) const REG_IND_ACC_CTL: usize = 0xb0;
+    pub(crate) const REG_IND_ACC_CTL: u32 = 0xb0;
     pub(crate) const IA_READ: usize = 0;
     pub(crate) const IA_AUTO_INC: usize = 1;
     pub(crate) const IA_SEL: usize = 2;
 
-    pub(crate) const REG_IND_ACC_ADDR: usize = 0xb1;
+    pub(crate) const REG_IND_ACC_ADDR: u32 = 0xb1;
     pub(crate) const IA_ADDR: usize = 0;
 
-    pub(crate) const REG_IND_ACC_DATA: usize = 0xb2;
+    pub(crate) const REG_IND_ACC_DATA: u32 = 0xb2;
     pub(crate) const IA_DATA: usize = 0;
 
     pub(crate) const REG_BIST_CONTROL: u32 = 0xb3;
@@ -531,7 +537,7 @@ mod ti954 {
     pub(crate) const IDX: usize = 4;
     pub(crate) const IDX_DONE: usize = 7;
 
-    pub(crate) const REG_LINK_ERROR_COUNT: usize = 0xb9;
+    pub(crate) const REG_LINK_ERROR_COUNT: u32 = 0xb9;
     pub(crate) const LINK_ERR_THRESH: usize = 0;
     pub(crate) const LINK_ERR_COUNT_EN: usize = 4;
     pub(crate) const LINK_SFIL_WAIT: usize = 5;
@@ -539,10 +545,10 @@ mod ti954 {
     pub(crate) const REG_FPD3_ENC_CTL: usize = 0xba;
     pub(crate) const FPD3_ENC_CRC_DIS: usize = 7;
 
-    pub(crate) const REG_FV_MIN_TIME: usize = 0xbc;
+    pub(crate) const REG_FV_MIN_TIME: u32 = 0xbc;
     pub(crate) const FRAME_VALID_MIN: usize = 0;
 
-    pub(crate) const REG_GPIO_PD_CTL: usize = 0xbe;
+    pub(crate) const REG_GPIO_PD_CTL: u32 = 0xbe;
     pub(crate) const GPIO0_PD_DIS: usize = 0;
     pub(crate) const GPIO1_PD_DIS: usize = 1;
     pub(crate) const GPIO2_PD_DIS: usize = 2;
@@ -556,7 +562,7 @@ mod ti954 {
     pub(crate) const FORCE_BC_ERRORS: usize = 1;
     pub(crate) const SER_BIST_ACT: usize = 5;
 
-    pub(crate) const REG_AEQ_CTL2: usize = 0xd2;
+    pub(crate) const REG_AEQ_CTL2: u32 = 0xd2;
     pub(crate) const SET_AEQ_FLOOR: usize = 2;
     pub(crate) const AEQ_RESTART: usize = 3;
     pub(crate) const AEQ_1ST_LOCK_MODE: usize = 4;
@@ -644,6 +650,7 @@ mod ti954 {
     pub(crate) const REG_FPD3_RX_ID5: usize = 0xf5;
     pub(crate) const FPD3_RX_ID5: usize = 0;
     pub(crate) const RX_ID_LENGTH: usize = 6;
+    pub(crate) const RX_ID: &[u8; RX_ID_LENGTH] = b"_UB954";
 
     pub(crate) const REG_I2C_RX0_ID: usize = 0xf8;
     pub(crate) const RX_PORT0_ID: usize = 1;
@@ -652,7 +659,7 @@ mod ti954 {
     pub(crate) const RX_PORT1_ID: usize = 1;
 
     // Indirect Register Map Description
-    pub(crate) const REG_IA_PATTERN_GEN_PAGE_BLOCK_SELECT: usize = 0x0;
+    pub(crate) const REG_IA_PATTERN_GEN_PAGE_BLOCK_SELECT: u32 = 0x0;
 
     pub(crate) const REG_IA_PGEN_CTL: u32 = 0x01;
     pub(crate) const PGEB_ENABLE: u8 = 0;
@@ -791,8 +798,10 @@ mod ti953 {
     pub(crate) const CSI_LANE_SEL2: u32 = 1;
     pub(crate) const CSI_LANE_SEL4: u32 = 3;
 
-    pub(crate) const REG_MODE_SEL: usize = 0x03;
+    pub(crate) const REG_MODE_SEL: u32 = 0x03;
     pub(crate) const MODE: usize = 0;
+    pub(crate) const MODE_SYNC: u32 = 0b000;
+    pub(crate) const MODE_DVP: u32 = 0b101;
     pub(crate) const MODE_DONE: usize = 3;
     pub(crate) const MODE_OV: usize = 4;
 
@@ -852,7 +861,7 @@ mod ti953 {
     pub(crate) const GPIO2_OUT_EN: usize = 6;
     pub(crate) const GPIO3_OUT_EN: usize = 7;
 
-    pub(crate) const REG_DVP_CFG: usize = 0x10;
+    pub(crate) const REG_DVP_CFG: u32 = 0x10;
     pub(crate) const DVP_LV_INV: usize = 0;
     pub(crate) const DVP_FV_IN: usize = 1;
     pub(crate) const DVP_DT_YUV_EN: usize = 2;
@@ -913,14 +922,14 @@ mod ti953 {
     pub(crate) const LINK_DETECT_EN: usize = 0;
     pub(crate) const CRC_ER_EN: usize = 1;
 
-    pub(crate) const REG_CSI_POL_SEL: usize = 0x20;
+    pub(crate) const REG_CSI_POL_SEL: u32 = 0x20;
     pub(crate) const POLARITY_D0: usize = 0;
     pub(crate) const POLARITY_D1: usize = 1;
     pub(crate) const POLARITY_D2: usize = 2;
     pub(crate) const POLARITY_D3: usize = 3;
     pub(crate) const POLARITY_CK0: usize = 4;
 
-    pub(crate) const REG_CSI_LP_POLARITY: usize = 0x21;
+    pub(crate) const REG_CSI_LP_POLARITY: u32 = 0x21;
     pub(crate) const POL_LP_DATA: usize = 0;
     pub(crate) const POL_LP_CLK0: usize = 4;
 
@@ -1081,15 +1090,15 @@ mod ti953 {
     pub(crate) const CSI2_ECC: usize = 0;
     pub(crate) const LINE_LENGTH_CHANGE: usize = 7;
 
-    pub(crate) const REG_IND_ACC_CTL: usize = 0xb0;
+    pub(crate) const REG_IND_ACC_CTL: u32 = 0xb0;
     pub(crate) const IA_READ: usize = 0;
     pub(crate) const IA_AUTO_INC: usize = 1;
     pub(crate) const IA_SEL: usize = 2;
 
-    pub(crate) const REG_IND_ACC_ADDR: usize = 0xb1;
+    pub(crate) const REG_IND_ACC_ADDR: u32 = 0xb1;
     pub(crate) const IND_ACC_ADDR: usize = 0;
 
-    pub(crate) const REG_IND_ACC_DATA: usize = 0xb2;
+    pub(crate) const REG_IND_ACC_DATA: u32 = 0xb2;
     pub(crate) const IND_ACC_DATA: usize = 0;
 
     pub(crate) const REG_FPD3_RX_ID0: u32 = 0xf0;
@@ -1102,7 +1111,7 @@ mod ti953 {
     pub(crate) const FPD3_RX_ID3: usize = 0;
     pub(crate) const REG_FPD3_RX_ID4: usize = 0xf4;
     pub(crate) const FPD3_RX_ID4: usize = 0;
-    pub(crate) const REG_FPD3_RX_ID5: usize = 0xf5;
+    pub(crate) const REG_FPD3_RX_ID5: u32 = 0xf5;
     pub(crate) const FPD3_RX_ID5: usize = 0;
     pub(crate) const RX_ID_LENGTH: usize = 6;
 }
@@ -1131,59 +1140,87 @@ mod ti953 {
     [(of::DeviceId::new(c_str!("ti,ds90ub954")), ()),]
 );
 
-const REGMAP_CONFIG: regmap::Config = regmap::Config::new(8, 8);
-
-static DS90UB95X_TP_REG_VAL: [(u32, u32); 31] = [
-    // Indirect Pattern Gen Registers
-    (0xB0, 0x00),
-    (0xB1, ti954::REG_IA_PGEN_CTL),
-    (0xB2, (1 << ti954::PGEB_ENABLE)),
-    (0xB1, ti954::REG_IA_PGEB_CFG),
-    (0xB2, 0x35),
-    (0xB1, ti954::REG_IA_PGEN_CSI_DI),
-    (0xB2, 0x2B),
-    (0xB1, ti954::REG_IA_PGEN_LINE_SIZE1),
-    (0xB2, 0x14),
-    (0xB1, ti954::REG_IA_PGEN_LINE_SIZE0),
-    (0xB2, 0x00),
-    (0xB1, ti954::REG_IA_PGEN_BAR_SIZE1),
-    (0xB2, 0x02),
-    (0xB1, ti954::REG_IA_PGEN_BAR_SIZE0),
-    (0xB2, 0x80),
-    (0xB1, ti954::REG_IA_PGEN_ACT_LPF1),
-    (0xB2, 0x08),
-    (0xB1, ti954::REG_IA_PGEN_ACT_LPF0),
-    (0xB2, 0x70),
-    (0xB1, ti954::REG_IA_PGEN_TOT_LPF1),
-    (0xB2, 0x08),
-    (0xB1, ti954::REG_IA_PGEN_TOT_LPF0),
-    (0xB2, 0x70),
-    (0xB1, ti954::REG_IA_PGEN_LINE_PD1),
-    (0xB2, 0x0B),
-    (0xB1, ti954::REG_IA_PGEN_LINE_PD0),
-    (0xB2, 0x93),
-    (0xB1, ti954::REG_IA_PGEN_VBP),
-    (0xB2, 0x21),
-    (0xB1, ti954::REG_IA_PGEN_VFP),
-    (0xB2, 0x0A),
-];
+const REGMAP_CONFIG: regmap::Config =
+    regmap::Config::new(8, 8).with_access_ops::<Ds90ub954AccessOps>();
+const SER_REGMAP_CONFIG: regmap::Config =
+    regmap::Config::new(8, 8).clone_with_max_register(ti953::REG_FPD3_RX_ID5);
+
+struct Ds90ub954AccessOps;
+
+impl regmap::ConfigOps for Ds90ub954AccessOps {
+    fn is_readable_reg(_reg: u32) -> bool {
+        true
+    }
+
+    fn is_writeable_reg(_reg: u32) -> bool {
+        true
+    }
+
+    fn is_volatile_reg(reg: u32) -> bool {
+        Self::is_precious_reg(reg)
+    }
+
+    // Interrupt status is cleared on read, so only the driver may read it.
+    fn is_precious_reg(reg: u32) -> bool {
+        reg == ti954::REG_CSI_TX_ISR
+    }
+}
+
+/// Pattern generator configuration, as pairs of indirect register and value.
+const DS90UB95X_TP_REG_VAL: RegTable<15> = RegTable::new(&[
+    (ti954::REG_IA_PGEN_CTL, pgen_ctl(true)),
+    (ti954::REG_IA_PGEB_CFG, 0x35),
+    (ti954::REG_IA_PGEN_CSI_DI, 0x2B),
+    (ti954::REG_IA_PGEN_LINE_SIZE1, 0x14),
+    (ti954::REG_IA_PGEN_LINE_SIZE0, 0x00),
+    (ti954::REG_IA_PGEN_BAR_SIZE1, 0x02),
+    (ti954::REG_IA_PGEN_BAR_SIZE0, 0x80),
+    (ti954::REG_IA_PGEN_ACT_LPF1, 0x08),
+    (ti954::REG_IA_PGEN_ACT_LPF0, 0x70),
+    (ti954::REG_IA_PGEN_TOT_LPF1, 0x08),
+    (ti954::REG_IA_PGEN_TOT_LPF0, 0x70),
+    (ti954::REG_IA_PGEN_LINE_PD1, 0x0B),
+    (ti954::REG_IA_PGEN_LINE_PD0, 0x93),
+    (ti954::REG_IA_PGEN_VBP, 0x21),
+    (ti954::REG_IA_PGEN_VFP, 0x0A),
+]);
 
 struct Ds90ub954 {
+    // Behind `Arc<Mutex<_>>` so that interrupt handlers and work items can take their own
+    // reference, once there are any.
+    data: Arc<Mutex<Ds90ub954Data>>,
+}
+
+// Fields are dropped in declaration order after `Drop::drop` powered the device down. The
+// serializers are reached through the deserializer, so they must go before its regmap and client.
+struct Ds90ub954Data {
+    serializers: [Option<Ds90ub953>; NUM_SERIALIZER],
+    regmap: regmap::Regmap,
     i2c_client: i2c::Client,
     // We store the GPIO descriptors here so gpiod_put is called when the driver
     // is removed.
-    _pass_gpio: Option<gpio::Desc>,
-    _lock_gpio: Option<gpio::Desc>,
+    pass_gpio: Option<gpio::Desc>,
+    lock_gpio: Option<gpio::Desc>,
     pdb_gpio: Option<gpio::Desc>,
-    regmap: regmap::Regmap,
-    serializers: [Option<Ds90ub953>; NUM_SERIALIZER],
+    power_state: PowerState,
     selected_rx_port: Option<RxPort>,
-    // This is used in the original C driver for some debugging code
-    _selected_ia_config: Option<u32>,
+    // `REG_IND_ACC_CTL` value last written by `write_ia_ctl`
+    selected_ia_config: Option<u32>,
     csi_lane_count: u32,
-    csi_lane_speed: u32,
+    csi_lane_speed: CsiLaneSpeed,
     test_pattern: bool,
     continuous_clock: bool,
+    output_mode: OutputMode,
+    refclk_mhz: u8,
+    link_error_threshold: Option<u8>,
+    gpio_pulldown_disable: u8,
+    force_refclk_detect: bool,
+    round_robin_weight: Option<RrWeight>,
+    bcc_watchdog_timeout: Option<BccWdTimeout>,
+    bcc_watchdog_disable: bool,
+    fv_min_time: Option<u8>,
+    csi_replicate: bool,
+    csi_lane_partition: LanePartition,
 }
 
 impl i2c::Driver for Ds90ub954 {
@@ -1196,6 +1233,12 @@ fn probe(client: &mut i2c::Client, id_info: Option<&Self::IdInfo>) -> Result<Pin
         pr_info!("probing ds90ub954\n");
 
         let dev = client.as_ref();
+        dev_info!(
+            dev,
+            "ds90ub954 at 0x{:02x} on i2c-{}\n",
+            client.addr(),
+            client.adapter_nr()
+        );
         let Some(_id_info) = id_info else {
             dev_err!(dev, "Failed to find matching dt id\n");
             return Err(ENODEV);
@@ -1212,13 +1255,24 @@ fn probe(client: &mut i2c::Client, id_info: Option<&Self::IdInfo>) -> Result<Pin
             csi_lane_speed,
             test_pattern,
             continuous_clock,
+            output_mode,
+            refclk_mhz,
+            link_error_threshold,
+            gpio_pulldown_disable,
+            force_refclk_detect,
+            round_robin_weight,
+            bcc_watchdog_timeout,
+            bcc_watchdog_disable,
+            fv_min_time,
+            csi_replicate,
+            csi_lane_partition,
         } = ds90ub954_parse_dt(dev).map_err(|err| {
             dev_err!(dev, "error parsing device tree\n");
             err
         })?;
 
         let regmap = regmap::Regmap::init_i2c(client, &REGMAP_CONFIG).map_err(|err| {
-            dev_err!(dev, "regmap init failed ({})\n", err.to_errno());
+            dev_err!(dev, "regmap init failed ({err})\n");
             err
         })?;
 
@@ -1227,65 +1281,153 @@ fn probe(client: &mut i2c::Client, id_info: Option<&Self::IdInfo>) -> Result<Pin
             err
         })?;
 
-        let driver_data = Self {
+        for ds90ub953 in serializers.iter().flatten() {
+            if !csi_lane_counts_compatible(csi_lane_count, ds90ub953.csi_lane_count) {
+                dev_warn!(
+                    dev,
+                    "serializer on rx_port {} uses {} csi lanes, but only {csi_lane_count} are forwarded\n",
+                    ds90ub953.rx_channel,
+                    ds90ub953.csi_lane_count
+                );
+            }
+        }
+
+        if let [Some(ser0), Some(ser1)] = &serializers {
+            if vc_maps_collide(ser0.virtual_channel_map, ser1.virtual_channel_map) {
+                dev_warn!(
+                    dev,
+                    "both serializers map VC-ID 0 to {}, set distinct virtual-channel-map values\n",
+                    decode_vc_map(ser0.virtual_channel_map)[0]
+                );
+            }
+        }
+
+        let driver_data = Ds90ub954Data {
             i2c_client: client.clone(),
-            _pass_gpio: pass_gpio,
-            _lock_gpio: lock_gpio,
+            pass_gpio,
+            lock_gpio,
             pdb_gpio,
+            power_state: PowerState::Off,
             regmap,
             serializers,
             selected_rx_port,
-            _selected_ia_config: selected_ia_config,
+            selected_ia_config,
             csi_lane_count,
             csi_lane_speed,
             test_pattern,
             continuous_clock,
+            output_mode,
+            refclk_mhz,
+            link_error_threshold,
+            gpio_pulldown_disable,
+            force_refclk_detect,
+            round_robin_weight,
+            bcc_watchdog_timeout,
+            bcc_watchdog_disable,
+            fv_min_time,
+            csi_replicate,
+            csi_lane_partition,
         };
-        let mut driver_data = KBox::new(driver_data, GFP_KERNEL)?;
+        let data = Arc::pin_init(new_mutex!(driver_data), GFP_KERNEL)?;
 
-        driver_data.pwr_enable();
+        {
+            let mut driver_data = data.lock();
 
-        kernel::delay::msleep(6); // wait for sensor to start
+            driver_data.pwr_enable();
 
-        driver_data.init()?;
+            kernel::delay::msleep(6); // wait for sensor to start
 
-        kernel::delay::msleep(500);
+            driver_data.init()?;
 
-        // init serializers
-        for i in 0..driver_data.serializers.len() {
-            // check if serializer is initialized
-            let Some(ds90ub953) = driver_data.serializers[i].as_mut() else {
-                continue;
-            };
-            // init serializer
-            if ds90ub953.init().is_err() {
-                dev_info!(
-                    driver_data.i2c_client.as_ref(),
-                    "init serializer {i} failed\n"
-                );
+            kernel::delay::msleep(500);
+
+            // init serializers
+            for i in 0..driver_data.serializers.len() {
+                // check if serializer is initialized
+                let Some(ds90ub953) = driver_data.serializers[i].as_mut() else {
+                    continue;
+                };
+                // init serializer
+                if ds90ub953.init().is_err() {
+                    dev_info!(
+                        driver_data.i2c_client.as_ref(),
+                        "init serializer {i} failed\n"
+                    );
+                }
             }
-        }
 
-        kernel::delay::msleep(500);
+            kernel::delay::msleep(500);
+        }
 
         // TODO enable sysfs tp ?
 
         pr_info!("done probing ds90ub954\n");
-        Ok(driver_data.into())
+        Ok(KBox::new(Self { data }, GFP_KERNEL)?.into())
+    }
+
+    fn remove(self: Pin<&mut Self>, client: &mut i2c::Client) {
+        dev_info!(client.as_ref(), "removing ds90ub954\n");
+        let mut data = self.data.lock();
+        data.shutdown();
+        data.pwr_disable();
     }
 }
 
-impl Ds90ub954 {
+impl Ds90ub954Data {
     fn pwr_enable(&mut self) {
-        if let Some(pdb_gpio) = &mut self.pdb_gpio {
-            pdb_gpio.set_value_cansleep(1);
-        }
+        self.set_power_state(PowerState::On);
     }
 
     fn pwr_disable(&mut self) {
-        if let Some(pdb_gpio) = &mut self.pdb_gpio {
-            pdb_gpio.set_value_cansleep(0);
+        self.set_power_state(PowerState::Off);
+    }
+
+    /// Stop forwarding from all serializers, disable their RX ports and the CSI output, and
+    /// release the serializers.
+    ///
+    /// This is best effort, failures are logged and the remaining steps are done anyway.
+    fn shutdown(&mut self) {
+        if self.power_state == PowerState::On {
+            for i in 0..NUM_SERIALIZER {
+                let Some(rx_port) = self.serializers[i].as_ref().map(|ser| ser.rx_channel) else {
+                    continue;
+                };
+                let fwd_dis = 1 << (ti954::FWD_PORT0_DIS + rx_port.to_u32());
+                if let Err(err) = self.update_bits(ti954::REG_FWD_CTL1, fwd_dis, fwd_dis) {
+                    pr_warn!("failed to disable forwarding of rx_port {rx_port} ({err})\n");
+                }
+                let port_en = 1 << (ti954::PORT0_EN + rx_port.to_u32());
+                if let Err(err) = self.update_bits(ti954::REG_RX_PORT_CTL, port_en, 0) {
+                    pr_warn!("failed to disable rx_port {rx_port} ({err})\n");
+                }
+            }
+            if let Err(err) = self.update_bits(ti954::REG_CSI_CTL, 1 << ti954::CSI_ENABLE, 0) {
+                pr_warn!("failed to disable csi output ({err})\n");
+            }
+        }
+
+        // Unregisters the dummy I2C clients of the serializers.
+        self.serializers = [const { None }; NUM_SERIALIZER];
+    }
+
+    /// Drive the PDB pin to `target`, doing nothing if the device is already in that state.
+    fn set_power_state(&mut self, target: PowerState) {
+        if !self.power_state.needs_transition(target) {
+            return;
+        }
+        let dev = self.i2c_client.as_ref();
+
+        match &mut self.pdb_gpio {
+            Some(pdb_gpio) => {
+                pdb_gpio.set_value_cansleep(target.to_pdb_value());
+                dev_info!(dev, "power {:?} -> {target:?}\n", self.power_state);
+            }
+            None if target == PowerState::On => {
+                dev_info!(dev, "no pdb-gpio, power is assumed to be always on\n");
+            }
+            None => {}
         }
+        self.power_state = target;
     }
 
     fn init(&mut self) -> Result<()> {
@@ -1297,31 +1439,44 @@ fn init(&mut self) -> Result<()> {
         let rev = self.read(ti954::REG_REVISION)?;
 
         let mut id_code = [0; ti954::RX_ID_LENGTH];
-        for (i, byte) in id_code.iter_mut().enumerate() {
-            *byte = self.read(ti954::REG_FPD3_RX_ID0 + i as u32)? as u8;
-        }
+        self.regmap
+            .bulk_read(ti954::REG_FPD3_RX_ID0, &mut id_code)
+            .map_err(|err| {
+                dev_err!(dev, "cannot read ID code ({err})!\n");
+                err
+            })?;
+        let is_ds90ub954 = id_code == *ti954::RX_ID;
         let id_code = BStr::from_bytes(&id_code);
 
         dev_info!(
             dev,
             "device ID: 0x{dev_id:x}, code: {id_code}, revision: 0x{rev:x}\n"
         );
+        if !is_ds90ub954 {
+            dev_err!(
+                dev,
+                "unexpected ID code {id_code}, is the device tree address of the ds90ub954 right?\n"
+            );
+            return Err(ENODEV);
+        }
 
         // disable builtin self test
         self.write(ti954::REG_BIST_CONTROL, 0)?;
 
-        // set CSI speed (REFCLK 25 MHz)
-        //  00 : 1.6 Gbps serial rate
-        //  01 : Reserved
-        //  10 : 800 Mbps serial rate
-        //  11 : 400 Mbps serial rate
-        let value = match self.csi_lane_speed {
-            400 => 0x3,
+        self.set_refclk_freq(self.refclk_mhz)?;
 
-            800 => 0x2,
-            _ => 0x0,
-        };
-        self.write(ti954::REG_CSI_PLL_CTL, value << ti954::CSI_TX_SPEED)?;
+        self.force_refclk_detect(self.force_refclk_detect)?;
+        if !self.force_refclk_detect
+            && self.read(ti954::REG_DEVICE_STS)? & (1 << ti954::REFCLK_VALID) == 0
+        {
+            dev_warn!(dev, "REFCLK not detected, consider force-refclk-detect\n");
+        }
+
+        // set CSI speed
+        self.write(
+            ti954::REG_CSI_PLL_CTL,
+            self.csi_lane_speed.to_csi_tx_speed() << ti954::CSI_TX_SPEED,
+        )?;
 
         // TODO add debug stuff? or just omit it?
 
@@ -1340,20 +1495,46 @@ fn init(&mut self) -> Result<()> {
                 | (1 << ti954::CSI_CAL_EN),
         )?;
 
-        kernel::delay::msleep(500);
+        if self.csi_lane_partition == LanePartition::Split {
+            self.set_csi_lane_partition(LanePartition::Split)?;
+        }
 
-        // check if test pattern should be turned on
-        if self.test_pattern {
-            dev_info!(dev, "deserializer init testpattern\n");
-            let _ = self.init_testpattern().map_err(|_| {
-                dev_info!(dev, "deserializer init testpattern failed\n");
-            });
+        self.set_output_mode(self.output_mode)?;
+
+        if let Some(weight) = self.round_robin_weight {
+            self.set_round_robin_weight(weight)?;
+        }
+
+        if self.csi_replicate {
+            self.set_csi_replicate(true)?;
         }
 
+        if self.bcc_watchdog_timeout.is_some() || self.bcc_watchdog_disable {
+            let timeout = self.bcc_watchdog_timeout.unwrap_or(BccWdTimeout::DEFAULT);
+            self.set_bcc_watchdog(timeout, self.bcc_watchdog_disable)?;
+        }
+
+        if let Some(ticks) = self.fv_min_time {
+            self.set_fv_min_time(ticks)?;
+        }
+
+        kernel::delay::msleep(500);
+
+        // Also switch the pattern generator off explicitly, without a PDB gpio it may still be
+        // running from a previous probe.
+        dev_info!(dev, "deserializer test pattern: {}\n", self.test_pattern);
+        let _ = self.set_test_pattern(self.test_pattern).map_err(|_| {
+            dev_info!(dev, "deserializer test pattern setup failed\n");
+        });
+
         // Setting PASS and LOCK to "all enabled receiver ports
         let value = 0b00111100;
         self.write(ti954::REG_RX_PORT_CTL, value)?;
 
+        if let Some(threshold) = self.link_error_threshold {
+            self.enable_link_error_counting(threshold)?;
+        }
+
         // for loop goes through each serializer
         for i in 0..self.serializers.len() {
             // Move ownership of serializer out of `self` temporarily, letting
@@ -1369,51 +1550,42 @@ fn init(&mut self) -> Result<()> {
 
             // Use closure for scoped early return and easy error-path cleanup.
             let mut init_serializer = || -> Result<()> {
-                // Get TI954_REG_RX_PORT_CTL and enable receiver rx_port
-                let mut value = self.read(ti954::REG_RX_PORT_CTL)?;
+                if let Some(ticks) = ds90ub953.aeq_relock_time {
+                    self.set_aeq_relock_time(rx_port, ticks)?;
+                }
 
-                value |= 1 << (ti954::PORT0_EN + rx_port.to_u32());
-                self.write(ti954::REG_RX_PORT_CTL, value)?;
+                // enable receiver rx_port
+                let port_en = 1 << (ti954::PORT0_EN + rx_port.to_u32());
+                self.update_bits(ti954::REG_RX_PORT_CTL, port_en, port_en)?;
 
                 // wait for receiver to calibrate link
                 kernel::delay::msleep(400);
 
-                // enable csi forwarding
-                let mut value = self.read(ti954::REG_FWD_CTL1)?;
+                // a marginal link may still lock once the equalizer adapts again
+                if self.recover_link(rx_port)? {
+                    dev_warn!(dev, "rx_port {rx_port} not locked, restarted AEQ\n");
+                    kernel::delay::msleep(400);
+                }
 
-                value &= 0xEF << rx_port.to_u32();
-                self.write(ti954::REG_FWD_CTL1, value)?;
+                // don't wait for the back channel of a serializer that isn't there
+                if !self.serializer_present(rx_port)? {
+                    dev_info!(dev, "no serializer locked on rx_port {rx_port}, skipping\n");
+                    return Err(ENODEV);
+                }
 
-                kernel::delay::msleep(500);
+                // enable csi forwarding
+                let fwd_dis = 1 << (ti954::FWD_PORT0_DIS + rx_port.to_u32());
+                self.update_bits(ti954::REG_FWD_CTL1, fwd_dis, 0)?;
 
                 // config back channel RX port [specific register]
-                self.write_rx_port(
-                    rx_port,
-                    ti954::REG_BCC_CONFIG,
-                    (ti954::BC_FREQ_50M << ti954::BC_FREQ_SELECT)
-                        | (1 << ti954::BC_CRC_GENERAOTR_ENABLE)
-                        | (1 << ti954::BC_ALWAYS_ON)
-                        | (if ds90ub953.i2c_pass_through_all { 1 } else { 0 }
-                            << ti954::I2C_PASS_THROUGH_ALL)
-                        | (1 << ti954::I2C_PASS_THROUGH),
-                )?;
+                self.set_i2c_forwarding(rx_port, ds90ub953.i2c_forwarding)?;
 
                 // wait for back channel
-                let mut backchannel_setup_failed = true;
-                for i in 0..50 {
-                    kernel::delay::msleep(10);
-                    let value = self.read(ti954::REG_DEVICE_STS)?;
-                    dev_info!(dev, "DEVICE STS: 0x{value:02x}, id={i} x 10ms\n",);
-                    if (value & 0xff) == 0xdf {
-                        backchannel_setup_failed = false;
-                        dev_info!(dev, "backchannel is ready\n",);
-                        break;
-                    }
-                }
-                if backchannel_setup_failed {
+                if let Err(e) = self.wait_for_lock(rx_port, BACKCHANNEL_TIMEOUT_MS) {
                     dev_err!(dev, "Backchannel setup failed!\n");
-                    return Err(EIO);
+                    return Err(e);
                 }
+                dev_info!(dev, "backchannel is ready\n");
 
                 // setup i2c forwarding
                 self.write_rx_port(
@@ -1444,17 +1616,7 @@ fn init(&mut self) -> Result<()> {
                 }
 
                 // set i2c slave ids and aliases
-                for (i, (&slave, &alias)) in ds90ub953
-                    .i2c_slave
-                    .as_ref()
-                    .iter()
-                    .zip(ds90ub953.i2c_alias.as_ref())
-                    .enumerate()
-                {
-                    let (slave, alias) = (slave as u32, alias as u32);
-                    if slave == 0 {
-                        continue;
-                    }
+                for (i, &(slave, alias)) in ds90ub953.i2c_aliases.as_ref().iter().enumerate() {
                     self.write_rx_port(
                         rx_port,
                         ti954::REG_SLAVE_ID0 + i as u32,
@@ -1462,9 +1624,6 @@ fn init(&mut self) -> Result<()> {
                     )?;
                     dev_info!(dev, "slave id {i}: 0x{slave:X}\n");
 
-                    if alias == 0 {
-                        continue;
-                    }
                     self.write_rx_port(
                         rx_port,
                         ti954::REG_ALIAS_ID0 + i as u32,
@@ -1477,17 +1636,22 @@ fn init(&mut self) -> Result<()> {
                 self.write_rx_port(
                     rx_port,
                     ti954::REG_CSI_VC_MAP,
-                    ds90ub953.virtual_channel_map,
+                    ds90ub953.virtual_channel_map.into(),
                 )?;
 
-                let val = ds90ub953.virtual_channel_map & 0b11;
-                dev_info!(dev, "VC-ID 0 mapped to {val}\n");
-                let val = (ds90ub953.virtual_channel_map & 0b1100) >> 2;
-                dev_info!(dev, "VC-ID 1 mapped to {val}\n");
-                let val = (ds90ub953.virtual_channel_map & 0b110000) >> 4;
-                dev_info!(dev, "VC-ID 2 mapped to {val}\n");
-                let val = (ds90ub953.virtual_channel_map & 0b11000000) >> 6;
-                dev_info!(dev, "VC-ID 3 mapped to {val}\n");
+                for (vc, val) in decode_vc_map(ds90ub953.virtual_channel_map)
+                    .iter()
+                    .enumerate()
+                {
+                    dev_info!(dev, "VC-ID {vc} mapped to {val}\n");
+                }
+
+                self.set_pass_control(rx_port, ds90ub953.pass_control)?;
+                self.set_csi_forward_options(rx_port, ds90ub953.csi_forward_options)?;
+
+                if let Some(dtype) = ds90ub953.embedded_dtype {
+                    self.set_embedded_dtype(rx_port, dtype, true)?;
+                }
 
                 // all rx_port specific registers set for rx_port X
                 dev_info!(dev, "init of deserializer rx_port {rx_port} successful\n");
@@ -1503,22 +1667,27 @@ fn init(&mut self) -> Result<()> {
                 dev_err!(dev, "deserializer rx_port {rx_port} is deactivated\n");
 
                 // DISABLE RX PORT
-                let Ok(mut val) = self.read(ti954::REG_RX_PORT_CTL) else {
-                    continue;
-                };
-                val &= 0xFF ^ (1 << (ti954::PORT0_EN + rx_port.to_u32()));
-                if self.write(ti954::REG_RX_PORT_CTL, val).is_err() {
+                let port_en = 1 << (ti954::PORT0_EN + rx_port.to_u32());
+                if self
+                    .update_bits(ti954::REG_RX_PORT_CTL, port_en, 0)
+                    .is_err()
+                {
                     continue;
                 }
                 // DISABLE CSI FORWARDING
-                let Ok(mut val) = self.read(ti954::REG_FWD_CTL1) else {
-                    continue;
-                };
-                val |= 1 << (ti954::FWD_PORT0_DIS + rx_port.to_u32());
-                let _ = self.write(ti954::REG_FWD_CTL1, val);
+                let fwd_dis = 1 << (ti954::FWD_PORT0_DIS + rx_port.to_u32());
+                let _ = self.update_bits(ti954::REG_FWD_CTL1, fwd_dis, fwd_dis);
             }
         }
 
+        if let Err(err) = require_serializer(&self.serializers) {
+            dev_err!(
+                dev,
+                "no serializer initialized, nothing to forward to the csi output\n"
+            );
+            return Err(err);
+        }
+
         // setup gpio forwarding, default all input
         self.write(
             ti954::REG_GPIO_INPUT_CTL,
@@ -1530,177 +1699,1422 @@ fn init(&mut self) -> Result<()> {
                 | (1 << ti954::GPIO1_INPUT_EN)
                 | (1 << ti954::GPIO0_INPUT_EN),
         )?;
-        self.write(ti954::REG_GPIO0_PIN_CTL, 0)?;
-        self.write(ti954::REG_GPIO1_PIN_CTL, 0)?;
-        self.write(ti954::REG_GPIO2_PIN_CTL, 0)?;
-        self.write(ti954::REG_GPIO3_PIN_CTL, 0)?;
-        self.write(ti954::REG_GPIO4_PIN_CTL, 0)?;
-        self.write(ti954::REG_GPIO5_PIN_CTL, 0)?;
-        self.write(ti954::REG_GPIO6_PIN_CTL, 0)?;
+        self.regmap.multi_reg_write(&[
+            (ti954::REG_GPIO0_PIN_CTL, 0),
+            (ti954::REG_GPIO1_PIN_CTL, 0),
+            (ti954::REG_GPIO2_PIN_CTL, 0),
+            (ti954::REG_GPIO3_PIN_CTL, 0),
+            (ti954::REG_GPIO4_PIN_CTL, 0),
+            (ti954::REG_GPIO5_PIN_CTL, 0),
+            (ti954::REG_GPIO6_PIN_CTL, 0),
+        ])?;
+        self.write(ti954::REG_GPIO_PD_CTL, self.gpio_pulldown_disable.into())?;
+
+        // verify the CSI transmitter came up with the configured clock mode, if it didn't fall
+        // back to disabling calibration and then to a discontinuous clock
+        let mut status = self.csi_tx_status()?;
+        while !status.ready() {
+            let csi_ctl = self.read(ti954::REG_CSI_CTL)?;
+            let Some(fallback) = csi_ctl_fallback(csi_ctl) else {
+                dev_warn!(
+                    dev,
+                    "CSI TX port not ready ({status:?}), check {} clock configuration\n",
+                    if self.continuous_clock {
+                        "continuous"
+                    } else {
+                        "discontinuous"
+                    }
+                );
+                break;
+            };
+            dev_warn!(
+                dev,
+                "CSI TX port not ready ({status:?}), retrying with CSI_CTL 0x{fallback:02x}\n"
+            );
+            self.write(ti954::REG_CSI_CTL, fallback)?;
+            self.continuous_clock = fallback & (1 << ti954::CSI_CONTS_CLOCK) != 0;
+            kernel::delay::msleep(CSI_FALLBACK_SETTLE_MS);
+            status = self.csi_tx_status()?;
+        }
+
+        // acknowledge the causes latched while the transmitter was coming up
+        let irq = self.csi_tx_interrupt_status()?;
+        if irq.has_error() {
+            dev_warn!(dev, "CSI TX reported errors during init ({irq:?})\n");
+        }
 
         dev_info!(dev, "init ds90ub954 done\n");
         Ok(())
     }
 
-    fn read(&mut self, register: u32) -> Result<u32> {
-        self.regmap.read(register).map_err(|err| {
-            dev_err!(
+    fn update_bits(&mut self, register: u32, mask: u32, value: u32) -> Result<()> {
+        self.regmap
+            .update_bits(register, mask, value)
+            .map_err(|err| {
+                dev_err!(
+                    self.i2c_client.as_ref(),
+                    "cannot update register 0x{register:02x} ({err})!\n"
+                );
+                err
+            })
+    }
+
+    fn init_testpattern(&mut self) -> Result<()> {
+        self.write_test_pattern(true).map_err(|err| {
+            dev_info!(
                 self.i2c_client.as_ref(),
-                "cannot read register 0x{register:02x} ({err:?})!\n"
+                "954: enable test pattern failed\n"
             );
             err
-        })
-    }
+        })?;
+        dev_info!(self.i2c_client.as_ref(), "enable test pattern successful\n");
 
-    fn write(&mut self, register: u32, value: u32) -> Result<()> {
-        self.regmap.write(register, value).map_err(|err| {
-            dev_err!(
+        if let Err(err) = self.verify_testpattern() {
+            dev_warn!(
                 self.i2c_client.as_ref(),
-                "cannot write register 0x{register:02x} ({err:?})!\n"
+                "cannot read back test pattern ({err})\n"
             );
-            err
-        })
+        }
+        Ok(())
     }
 
-    #[allow(unused, reason = "used behind #ifdef DEBUG in C driver")]
-    fn read_rx_port(&mut self, rx_port: RxPort, addr: u32) -> Result<u32> {
-        let i2c_client = self.i2c_client.clone();
-        let dev = i2c_client.as_ref();
+    /// Read the pattern generator configuration back and warn about registers that don't hold
+    /// the value written by [`Self::init_testpattern`].
+    fn verify_testpattern(&mut self) -> Result<()> {
+        const START: u32 = ti954::REG_IA_PGEN_CTL;
+        let mut readback = [0u8; (ti954::REG_IA_PGEN_VFP - START + 1) as usize];
+        self.indirect_read_block(
+            ti954::REG_IA_PATTERN_GEN_PAGE_BLOCK_SELECT as u8,
+            START as u8,
+            &mut readback,
+        )?;
 
-        // Check if port is selected, select port if needed
-        if self.selected_rx_port != Some(rx_port) {
-            let port_reg = match rx_port {
-                RxPort::Zero => 0b1, // leave ti954::RX_READ_PORT at 0
-                RxPort::One => 0b10 | (1 << ti954::RX_READ_PORT),
-                RxPort::Both => {
-                    dev_err!(
-                        dev,
-                        "attempted to read from both rx ports at the same time\n"
-                    );
-                    0b1 // fallback to port 0
-                }
+        for &(register, expected) in &DS90UB95X_TP_REG_VAL.0 {
+            let Some(&value) = readback.get(register.wrapping_sub(START) as usize) else {
+                continue;
             };
+            if u32::from(value) != expected {
+                dev_warn!(
+                    self.i2c_client.as_ref(),
+                    "test pattern register 0x{register:02x} is 0x{value:02x}, expected 0x{expected:02x}\n"
+                );
+            }
+        }
+        Ok(())
+    }
 
-            self.write(ti954::REG_FPD3_PORT_SEL, port_reg)
-                .map_err(|err| {
-                    dev_err!(dev, "error writing register ti954::REG_FPD3_PORT_SEL\n",);
-                    err
-                })?;
-
-            self.selected_rx_port = Some(rx_port);
+    /// Switch the CSI output between the received video and the internal test pattern.
+    ///
+    /// Enabling writes the whole pattern generator configuration, so this also works if the
+    /// test pattern wasn't enabled at probe.
+    fn set_test_pattern(&mut self, enable: bool) -> Result<()> {
+        if enable {
+            return self.init_testpattern();
         }
-        self.read(addr).map_err(|err| {
-            dev_err!(dev, "error read register (0x{:02x})\n", addr);
-            err
-        })
+        self.write_test_pattern(false)
     }
 
-    fn write_rx_port(&mut self, rx_port: RxPort, addr: u32, value: u32) -> Result<()> {
-        let i2c_client = self.i2c_client.clone();
-        let dev = i2c_client.as_ref();
+    /// Configure the watchdog that terminates back channel transactions which take too long.
+    fn set_bcc_watchdog(&mut self, timeout: BccWdTimeout, disable: bool) -> Result<()> {
+        self.write(ti954::REG_BCC_WD_CTL, timeout.to_bcc_wd_ctl(disable))
+    }
 
-        // Check if port is selected, select port if needed
-        if self.selected_rx_port != Some(rx_port) {
-            let port_reg = match rx_port {
-                RxPort::Zero => 0b01, // set RX_WRITE_PORT_0
-                RxPort::One => 0b10,  // set RX_WRITE_PORT_1
-                RxPort::Both => 0b11, // set RX_WRITE_PORT_0 & 1
-            };
+    /// Set the minimum time frame valid has to be active for a frame to count, in FPD-Link III
+    /// clock cycles.
+    ///
+    /// Shorter frames don't latch the frame size, so together with `DISCARD_ON_FRAME_SIZE` in
+    /// `REG_PORT_CONFIG2` runt frames can be filtered out.
+    fn set_fv_min_time(&mut self, ticks: u8) -> Result<()> {
+        self.write(ti954::REG_FV_MIN_TIME, fv_min_time_reg(ticks))
+    }
 
-            self.write(ti954::REG_FPD3_PORT_SEL, port_reg)
-                .map_err(|err| {
-                    dev_err!(dev, "error writing register ti954::REG_FPD3_PORT_SEL\n",);
-                    err
-                })?;
+    /// Set the weighting of round robin forwarding of the RX ports to CSI port 0.
+    fn set_round_robin_weight(&mut self, weight: RrWeight) -> Result<()> {
+        let value = self.read(ti954::REG_FWD_CTL2)?;
+        self.write(ti954::REG_FWD_CTL2, weight.apply_to_fwd_ctl2(value))
+    }
 
-            self.selected_rx_port = Some(rx_port);
-        }
-        self.write(addr, value).map_err(|err| {
-            dev_err!(dev, "error writing register (0x{:02x})\n", addr);
-            err
-        })
+    /// Replicate the CSI-2 output of port 0 on port 1.
+    ///
+    /// The forwarding mode configured in `REG_FWD_CTL2` is kept. Replication needs RX ports to
+    /// be forwarded to CSI port 0, so if neither synchronized nor round robin forwarding is
+    /// selected, round robin forwarding is enabled as well.
+    fn set_csi_replicate(&mut self, enable: bool) -> Result<()> {
+        let value = self.read(ti954::REG_FWD_CTL2)?;
+        self.write(ti954::REG_FWD_CTL2, fwd_ctl2_replicate(value, enable))
     }
 
-    fn init_testpattern(&mut self) -> Result<()> {
-        for (reg, val) in DS90UB95X_TP_REG_VAL {
-            self.write(reg, val).map_err(|err| {
-                dev_info!(
-                    self.i2c_client.as_ref(),
-                    "954: enable test pattern failed\n"
-                );
-                err
-            })?;
-        }
-        dev_info!(self.i2c_client.as_ref(), "enable test pattern successful\n");
-        Ok(())
+    /// Put the CSI-2 output into the ultra-low-power state, or wake it up again.
+    ///
+    /// The other `REG_CSI_CTL` settings are kept.
+    #[allow(unused, reason = "no runtime control interface yet")]
+    fn set_csi_ulp(&mut self, enable: bool) -> Result<()> {
+        let value = self.read(ti954::REG_CSI_CTL)?;
+        self.write(ti954::REG_CSI_CTL, csi_ctl_ulp(value, enable))
     }
-}
 
-struct Ds90ub954ParseDtReturn {
-    pass_gpio: Option<gpio::Desc>,
-    lock_gpio: Option<gpio::Desc>,
-    pdb_gpio: Option<gpio::Desc>,
-    csi_lane_count: u32,
-    csi_lane_speed: u32,
-    test_pattern: bool,
-    continuous_clock: bool,
-}
-fn ds90ub954_parse_dt(dev: &kernel::device::Device) -> Result<Ds90ub954ParseDtReturn> {
-    let try_get_gpio = |con_id: &'static CStr, flags: gpio::Flags| -> Result<Option<gpio::Desc>> {
-        match gpio::Desc::get(dev, con_id, flags) {
-            Ok(gpio) => Ok(Some(gpio)),
-            Err(e) if e == EPROBE_DEFER => {
-                dev_err!(dev, "{con_id}-gpio read failed: (EPROBE_DEFER)\n");
-                Err(e)
-            }
-            Err(_) => {
-                dev_info!(dev, "{con_id}-gpio not found, ignoring\n");
-                Ok(None)
-            }
-        }
-    };
-    let pass_gpio = try_get_gpio(c_str!("pass"), gpio::Flags::In)?;
-    let lock_gpio = try_get_gpio(c_str!("lock"), gpio::Flags::In)?;
-    let pdb_gpio = try_get_gpio(c_str!("pdb"), gpio::Flags::OutLow)?;
+    /// Split the CSI-2 lanes between the two output ports, or give all of them to port 0.
+    ///
+    /// Splitting reduces port 0 to two lanes and replicates its output on the other two lanes
+    /// as port 1, see [`Self::set_csi_replicate`].
+    fn set_csi_lane_partition(&mut self, partition: LanePartition) -> Result<()> {
+        let value = self.read(ti954::REG_CSI_CTL)?;
+        self.write(ti954::REG_CSI_CTL, partition.apply_to_csi_ctl(value))?;
+        self.csi_lane_count = partition.lane_count();
 
-    let fwnode = dev.as_fwnode();
+        let value = self.read(ti954::REG_FWD_CTL2)?;
+        self.write(
+            ti954::REG_FWD_CTL2,
+            fwd_ctl2_replicate(value, partition == LanePartition::Split),
+        )
+    }
 
-    let csi_lane_count = fwnode
-        .property_read::<u32>(c_str!("csi-lane-count"), None)
-        .unwrap_or_else(|_| {
-            dev_info!(
-                dev,
-                "csi-lane-count property not found, set to default value\n"
-            );
-            4
-        });
-    dev_info!(dev, "csi-lane-count: {csi_lane_count}\n");
+    /// Let the receivers drop lock once more than `threshold` link errors were counted.
+    ///
+    /// Recovery from a lost lock is done by [`Self::recover_link`].
+    fn enable_link_error_counting(&mut self, threshold: u8) -> Result<()> {
+        let value = link_error_count(threshold).ok_or(EINVAL)?;
+        self.write_rx_port(RxPort::Both, ti954::REG_LINK_ERROR_COUNT, value)
+    }
 
-    let csi_lane_speed = fwnode
-        .property_read::<u32>(c_str!("csi-lane-speed"), None)
-        .unwrap_or_else(|_| {
-            dev_info!(
-                dev,
-                "csi-lane-speed property not found, set to default value\n"
-            );
-            1600
-        });
-    dev_info!(dev, "csi-lane-speed: {csi_lane_speed}\n");
+    /// Set the time the adaptive equalizer of `rx_port` waits for lock before restarting the
+    /// adaption, as the raw 3-bit `ADAPTIVE_EQ_RELOCK_TIME` value.
+    fn set_aeq_relock_time(&mut self, rx_port: RxPort, ticks: u8) -> Result<()> {
+        let value = self.read_rx_port(rx_port, ti954::REG_AEQ_CTL2)?;
+        let value = aeq_ctl2_relock_time(value, ticks).ok_or(EINVAL)?;
+        self.write_rx_port(rx_port, ti954::REG_AEQ_CTL2, value)
+    }
 
-    let test_pattern = fwnode.property_read_bool(c_str!("test-pattern"));
-    if test_pattern {
-        dev_info!(dev, "test-pattern enabled\n");
-    } else {
-        dev_info!(dev, "test-pattern disabled\n");
+    /// Wait until the receiver of `rx_port` is locked and passes the received data.
+    ///
+    /// Polls `REG_RX_PORT_STS1` every 10 ms and returns [`ETIMEDOUT`] if `LOCK_STS` and
+    /// `PORT_PASS` are not both set within `timeout_ms`.
+    fn wait_for_lock(&mut self, rx_port: RxPort, timeout_ms: u32) -> Result {
+        kernel::delay::read_poll_timeout(
+            || self.read_rx_port(rx_port, ti954::REG_RX_PORT_STS1),
+            |&sts1| rx_port_locked_and_passing(sts1),
+            10,
+            timeout_ms,
+        )?;
+
+        self.check_status_gpios(rx_port);
+        Ok(())
     }
 
-    let continuous_clock = fwnode.property_read_bool(c_str!("continuous-clock"));
+    /// Cross-check the `pass` and `lock` pins against the status registers after the receiver of
+    /// `rx_port` reported lock and pass.
+    ///
+    /// A mismatch only points at a wiring or pin configuration problem, so it is logged and
+    /// otherwise ignored.
+    fn check_status_gpios(&self, rx_port: RxPort) {
+        let dev = self.i2c_client.as_ref();
+        for (name, gpio) in [("pass", &self.pass_gpio), ("lock", &self.lock_gpio)] {
+            let Some(gpio) = gpio else {
+                continue;
+            };
+            match gpio.get_value_cansleep() {
+                Ok(true) => {}
+                Ok(false) => dev_warn!(
+                    dev,
+                    "rx_port {rx_port} is locked, but the {name} gpio is inactive\n"
+                ),
+                Err(e) => dev_warn!(dev, "failed to read {name} gpio ({e})\n"),
+            }
+        }
+    }
+
+    /// Read and acknowledge the pending interrupt causes of the CSI transmitter.
+    ///
+    /// Reading `REG_CSI_TX_ISR` clears it, so the causes returned here are only reported once.
+    fn csi_tx_interrupt_status(&mut self) -> Result<CsiTxIrq> {
+        Ok(CsiTxIrq::from_csi_tx_isr(self.read(ti954::REG_CSI_TX_ISR)?))
+    }
+
+    /// Read the synchronization and pass state of the CSI transmitter.
+    fn csi_tx_status(&mut self) -> Result<CsiTxStatus> {
+        Ok(CsiTxStatus::from_csi_sts(self.read(ti954::REG_CSI_STS)?))
+    }
+
+    /// Tell the deserializer the frequency of its REFCLK input.
+    fn set_refclk_freq(&mut self, mhz: u8) -> Result<()> {
+        self.write(ti954::REG_REFCLK_FREQ, refclk_freq_reg(mhz))
+    }
+
+    /// Configure how I2C traffic is forwarded to the remote side of an RX port.
+    ///
+    /// This rewrites the whole back channel configuration of the port, the
+    /// remaining bits are always set to the values used during [`Self::init`].
+    fn set_i2c_forwarding(&mut self, rx_port: RxPort, cfg: I2cFwd) -> Result<()> {
+        self.write_rx_port(
+            rx_port,
+            ti954::REG_BCC_CONFIG,
+            (ti954::BC_FREQ_50M << ti954::BC_FREQ_SELECT)
+                | (1 << ti954::BC_CRC_GENERAOTR_ENABLE)
+                | (1 << ti954::BC_ALWAYS_ON)
+                | cfg.to_bcc_config(),
+        )
+    }
+
+    /// Configure when the PASS signal of an RX port asserts.
+    fn set_pass_control(&mut self, rx_port: RxPort, cfg: PassControl) -> Result<()> {
+        self.write_rx_port(rx_port, ti954::REG_PORT_PASS_CTL, cfg.to_port_pass_ctl())
+    }
+
+    /// Configure which CSI-2 packets received on an RX port are forwarded.
+    ///
+    /// The FPD-Link III mode bits of `REG_PORT_CONFIG` are kept.
+    fn set_csi_forward_options(&mut self, rx_port: RxPort, opts: CsiFwdOpts) -> Result<()> {
+        let value = self.read_rx_port(rx_port, ti954::REG_PORT_CONFIG)?;
+        self.write_rx_port(
+            rx_port,
+            ti954::REG_PORT_CONFIG,
+            opts.apply_to_port_config(value),
+        )
+    }
+
+    /// Configure the behavior of the CSI output pins.
+    fn set_output_mode(&mut self, mode: OutputMode) -> Result<()> {
+        let mut value = self.read(ti954::REG_GENERAL_CFG)?;
+        value &= !((1 << ti954::OUTPUT_ENABLE)
+            | (1 << ti954::OUTPUT_EN_MODE)
+            | (1 << ti954::OUTPUT_SLEEP_STATE_SELECT));
+        value |= mode.to_general_cfg();
+        self.write(ti954::REG_GENERAL_CFG, value)
+    }
+
+    /// Treat REFCLK as valid, regardless of the deserializer's own detection.
+    fn force_refclk_detect(&mut self, force: bool) -> Result<()> {
+        let value = self.read(ti954::REG_GENERAL_CFG)?;
+        self.write(
+            ti954::REG_GENERAL_CFG,
+            general_cfg_force_refclk(value, force),
+        )
+    }
+
+    /// Trigger a single CSI PHY calibration or enable periodic calibration.
+    ///
+    /// Useful when the attached sensor is switched at runtime.
+    #[allow(unused, reason = "no runtime control interface yet")]
+    fn csi_recalibrate(&mut self, mode: CalMode) -> Result<()> {
+        // calibration must be enabled for the CSI_CTL2 bits to have any effect
+        let value = self.read(ti954::REG_CSI_CTL)?;
+        if value & (1 << ti954::CSI_CAL_EN) == 0 {
+            self.write(ti954::REG_CSI_CTL, value | (1 << ti954::CSI_CAL_EN))?;
+        }
+
+        let value = self.read(ti954::REG_CSI_CTL2)?;
+        self.write(ti954::REG_CSI_CTL2, csi_ctl2_cal(value, mode))?;
+
+        dev_info!(self.i2c_client.as_ref(), "CSI calibration: {mode:?}\n");
+        Ok(())
+    }
+
+    /// Configure the CSI-2 data type identifying embedded data lines sent by the sensor.
+    fn set_embedded_dtype(&mut self, rx_port: RxPort, dtype: u8, enable: bool) -> Result<()> {
+        let value = raq_embed_dtype(dtype, enable).ok_or(EINVAL)?;
+        self.write_rx_port(rx_port, ti954::REG_RAQ_EMBED_DTYPE, value)
+    }
+
+    /// Write a byte to one of the two mailbox registers shared with the serializer side.
+    #[allow(unused, reason = "no runtime control interface yet")]
+    fn mailbox_write(&mut self, index: u8, val: u8) -> Result<()> {
+        let register = mailbox_reg(index).ok_or(EINVAL)?;
+        self.write(register, u32::from(val) << ti954::MAILBOX_0)
+    }
+
+    /// Read a byte from one of the two mailbox registers shared with the serializer side.
+    #[allow(unused, reason = "no runtime control interface yet")]
+    fn mailbox_read(&mut self, index: u8) -> Result<u8> {
+        let register = mailbox_reg(index).ok_or(EINVAL)?;
+        Ok((self.read(register)? >> ti954::MAILBOX_0) as u8)
+    }
+}
+
+/// Register accesses of the deserializer.
+///
+/// The access sequences are provided methods on top of single register reads and writes, so
+/// they can be tested on a mock register map.
+trait RegisterAccess {
+    /// Read `register`.
+    fn read(&mut self, register: u32) -> Result<u32>;
+
+    /// Write `value` to `register`.
+    fn write(&mut self, register: u32, value: u32) -> Result<()>;
+
+    /// Write the `(register, value)` pairs in order.
+    fn multi_reg_write(&mut self, regs: &[(u32, u32)]) -> Result<()>;
+
+    /// `REG_IND_ACC_CTL` value last written by [`Self::write_ia_ctl`], if it is known.
+    fn selected_ia_config(&mut self) -> &mut Option<u32>;
+
+    /// RX port last selected by [`Self::read_rx_port`] or [`Self::write_rx_port`], if it is
+    /// known.
+    fn selected_rx_port(&mut self) -> &mut Option<RxPort>;
+
+    /// Read the RX port register `addr` of `rx_port`, selecting the port first if needed.
+    ///
+    /// Returns [`EINVAL`] for [`RxPort::Both`], only writes can go to both ports.
+    fn read_rx_port(&mut self, rx_port: RxPort, addr: u32) -> Result<u32> {
+        // Check if port is selected, select port if needed
+        if *self.selected_rx_port() != Some(rx_port) {
+            let port_reg = match rx_port {
+                RxPort::Zero => 0b1, // leave ti954::RX_READ_PORT at 0
+                RxPort::One => 0b10 | (1 << ti954::RX_READ_PORT),
+                RxPort::Both => return Err(EINVAL),
+            };
+
+            // Forget the selection if the write fails, the register state is unknown then.
+            *self.selected_rx_port() = None;
+            self.write(ti954::REG_FPD3_PORT_SEL, port_reg)?;
+            *self.selected_rx_port() = Some(rx_port);
+        }
+        self.read(addr)
+    }
+
+    /// Write `value` to the RX port register `addr` of `rx_port`, selecting the port first if
+    /// needed.
+    fn write_rx_port(&mut self, rx_port: RxPort, addr: u32, value: u32) -> Result<()> {
+        // Check if port is selected, select port if needed
+        if *self.selected_rx_port() != Some(rx_port) {
+            // Also select the read port, `selected_rx_port` is shared with `read_rx_port`.
+            let port_reg = match rx_port {
+                RxPort::Zero => 0b01,                             // set RX_WRITE_PORT_0
+                RxPort::One => 0b10 | (1 << ti954::RX_READ_PORT), // set RX_WRITE_PORT_1
+                RxPort::Both => 0b11,                             // set RX_WRITE_PORT_0 & 1
+            };
+
+            *self.selected_rx_port() = None;
+            self.write(ti954::REG_FPD3_PORT_SEL, port_reg)?;
+            *self.selected_rx_port() = Some(rx_port);
+        }
+        self.write(addr, value)
+    }
+
+    /// Check whether the receiver of `rx_port` is locked to a serializer.
+    fn serializer_present(&mut self, rx_port: RxPort) -> Result<bool> {
+        let status = self.read_rx_port(rx_port, ti954::REG_RX_PORT_STS1)?;
+        Ok(rx_port_locked(status))
+    }
+
+    /// Restart adaptive equalization of an RX port whose receiver isn't locked.
+    ///
+    /// Returns whether a restart was triggered. There is no periodic link monitoring yet, this
+    /// is only done while bringing up a serializer.
+    fn recover_link(&mut self, rx_port: RxPort) -> Result<bool> {
+        if self.serializer_present(rx_port)? {
+            return Ok(false);
+        }
+        self.aeq_restart(rx_port)?;
+        Ok(true)
+    }
+
+    /// Restart the adaptive equalizer of `rx_port` from its initial value.
+    ///
+    /// The restart bit is pulsed, so the other `REG_AEQ_CTL2` settings are kept and a later
+    /// read-modify-write doesn't trigger another restart.
+    fn aeq_restart(&mut self, rx_port: RxPort) -> Result<()> {
+        let value = self.read_rx_port(rx_port, ti954::REG_AEQ_CTL2)?;
+        let [restart, done] = aeq_ctl2_restart_pulse(value);
+        self.write_rx_port(rx_port, ti954::REG_AEQ_CTL2, restart)?;
+        self.write_rx_port(rx_port, ti954::REG_AEQ_CTL2, done)
+    }
+
+    /// Select the indirect register page `page` for reading or writing, unless it already is.
+    fn select_ia_config(&mut self, page: u32, read: bool) -> Result<()> {
+        self.write_ia_ctl(ind_acc_ctl(page, read))
+    }
+
+    /// Write `ctl` to `REG_IND_ACC_CTL`, unless it already holds that value.
+    fn write_ia_ctl(&mut self, ctl: u32) -> Result<()> {
+        if needs_ia_select(*self.selected_ia_config(), ctl) {
+            // Forget the selection if the write fails, the register state is unknown then.
+            *self.selected_ia_config() = None;
+            self.write(ti954::REG_IND_ACC_CTL, ctl)?;
+            *self.selected_ia_config() = Some(ctl);
+        }
+        Ok(())
+    }
+
+    /// Read register `addr` of the indirect register page `page`.
+    #[allow(unused, reason = "no runtime control interface yet")]
+    fn indirect_read(&mut self, page: u32, addr: u32) -> Result<u32> {
+        self.select_ia_config(page, true)?;
+        self.write(ti954::REG_IND_ACC_ADDR, addr)?;
+        self.read(ti954::REG_IND_ACC_DATA)
+    }
+
+    /// Read `buf.len()` consecutive registers of the indirect register page `page`, starting at
+    /// `start`.
+    ///
+    /// The address auto-increments with every read of `REG_IND_ACC_DATA`, so it is only written
+    /// once. Returns [`EINVAL`] if the block extends past the end of the page.
+    fn indirect_read_block(&mut self, page: u8, start: u8, buf: &mut [u8]) -> Result<()> {
+        if !ia_block_fits(start.into(), buf.len()) {
+            return Err(EINVAL);
+        }
+        self.write_ia_ctl(ind_acc_ctl_auto_inc(page.into()))?;
+        self.write(ti954::REG_IND_ACC_ADDR, start.into())?;
+        for byte in buf.iter_mut() {
+            *byte = self.read(ti954::REG_IND_ACC_DATA)? as u8;
+        }
+        Ok(())
+    }
+
+    /// Write register `addr` of the indirect register page `page`.
+    fn indirect_write(&mut self, page: u32, addr: u32, value: u32) -> Result<()> {
+        self.select_ia_config(page, false)?;
+        self.write(ti954::REG_IND_ACC_ADDR, addr)?;
+        self.write(ti954::REG_IND_ACC_DATA, value)
+    }
+
+    /// Write the whole pattern generator configuration, which enables it, or only disable the
+    /// pattern generator.
+    fn write_test_pattern(&mut self, enable: bool) -> Result<()> {
+        if !enable {
+            return self.indirect_write(
+                ti954::REG_IA_PATTERN_GEN_PAGE_BLOCK_SELECT,
+                ti954::REG_IA_PGEN_CTL,
+                pgen_ctl(false),
+            );
+        }
+        let seq = indirect_write_sequence(
+            ti954::REG_IND_ACC_ADDR,
+            ti954::REG_IND_ACC_DATA,
+            &DS90UB95X_TP_REG_VAL,
+        );
+        self.select_ia_config(ti954::REG_IA_PATTERN_GEN_PAGE_BLOCK_SELECT, false)?;
+        self.multi_reg_write(seq.as_flattened())
+    }
+}
+
+impl RegisterAccess for Ds90ub954Data {
+    fn read(&mut self, register: u32) -> Result<u32> {
+        self.regmap.read(register).map_err(|err| {
+            dev_err!(
+                self.i2c_client.as_ref(),
+                "cannot read register 0x{register:02x} ({err})!\n"
+            );
+            err
+        })
+    }
+
+    fn write(&mut self, register: u32, value: u32) -> Result<()> {
+        self.regmap.write(register, value).map_err(|err| {
+            dev_err!(
+                self.i2c_client.as_ref(),
+                "cannot write register 0x{register:02x} ({err})!\n"
+            );
+            err
+        })
+    }
+
+    fn multi_reg_write(&mut self, regs: &[(u32, u32)]) -> Result<()> {
+        self.regmap.multi_reg_write(regs)
+    }
+
+    fn selected_ia_config(&mut self) -> &mut Option<u32> {
+        &mut self.selected_ia_config
+    }
+
+    fn selected_rx_port(&mut self) -> &mut Option<RxPort> {
+        &mut self.selected_rx_port
+    }
+}
+
+/// Encode `REG_RAQ_EMBED_DTYPE`, the data type is limited to 6 bits.
+const fn raq_embed_dtype(dtype: u8, enable: bool) -> Option<u32> {
+    if dtype >= 1 << (ti954::EMBED_DTYPE_EN - ti954::EMBED_DTYPE_ID) {
+        return None;
+    }
+    Some(((dtype as u32) << ti954::EMBED_DTYPE_ID) | ((enable as u32) << ti954::EMBED_DTYPE_EN))
+}
+
+kernel::static_assert!(matches!(raq_embed_dtype(0x12, true), Some(0x52)));
+kernel::static_assert!(matches!(raq_embed_dtype(0x12, false), Some(0x12)));
+kernel::static_assert!(matches!(raq_embed_dtype(0x3f, true), Some(0x7f)));
+kernel::static_assert!(raq_embed_dtype(0x40, true).is_none());
+
+/// State of the CSI transmitter, as reported by `REG_CSI_STS`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct CsiTxStatus {
+    /// Forwarded RX ports are synchronized, i.e. their frames line up.
+    sync: bool,
+    /// At least one forwarded RX port passes, i.e. sends valid video.
+    pass: bool,
+}
+
+impl CsiTxStatus {
+    const fn from_csi_sts(value: u32) -> Self {
+        Self {
+            sync: value & (1 << ti954::TX_PORT_SYNC) != 0,
+            pass: value & (1 << ti954::TX_PORT_PASS) != 0,
+        }
+    }
+
+    /// Whether the CSI transmitter is synchronized and forwards valid video.
+    const fn ready(self) -> bool {
+        self.sync && self.pass
+    }
+}
+
+/// Time the CSI transmitter gets to come up after falling back to another `REG_CSI_CTL` value.
+const CSI_FALLBACK_SETTLE_MS: u32 = 100;
+
+/// Next `REG_CSI_CTL` value to try when the CSI transmitter didn't come up with `value`.
+///
+/// Calibration is disabled first, then the clock is made discontinuous. Returns `None` when
+/// there is nothing left to fall back to.
+const fn csi_ctl_fallback(value: u32) -> Option<u32> {
+    if value & (1 << ti954::CSI_CAL_EN) != 0 {
+        Some(value & !(1 << ti954::CSI_CAL_EN))
+    } else if value & (1 << ti954::CSI_CONTS_CLOCK) != 0 {
+        Some(value & !(1 << ti954::CSI_CONTS_CLOCK))
+    } else {
+        None
+    }
+}
+
+kernel::static_assert!(CsiTxStatus::from_csi_sts(0b11).ready());
+kernel::static_assert!(!CsiTxStatus::from_csi_sts(0b01).ready());
+kernel::static_assert!(!CsiTxStatus::from_csi_sts(0b10).ready());
+kernel::static_assert!(matches!(csi_ctl_fallback(0x43), Some(0x03)));
+kernel::static_assert!(matches!(csi_ctl_fallback(0x03), Some(0x01)));
+kernel::static_assert!(matches!(csi_ctl_fallback(0x41), Some(0x01)));
+kernel::static_assert!(csi_ctl_fallback(0x01).is_none());
+kernel::static_assert!(csi_ctl_fallback(0x31).is_none());
+
+kernel::static_assert!(matches!(
+    CsiTxStatus::from_csi_sts(0b11),
+    CsiTxStatus {
+        sync: true,
+        pass: true
+    }
+));
+kernel::static_assert!(matches!(
+    CsiTxStatus::from_csi_sts(0b01),
+    CsiTxStatus {
+        sync: false,
+        pass: true
+    }
+));
+kernel::static_assert!(matches!(
+    CsiTxStatus::from_csi_sts(0xfc),
+    CsiTxStatus {
+        sync: false,
+        pass: false
+    }
+));
+
+/// Interrupt causes of the CSI transmitter, as reported by `REG_CSI_TX_ISR`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct CsiTxIrq {
+    /// The pass state of the CSI transmitter changed.
+    pass: bool,
+    /// A pass error occurred on one of the forwarded RX ports.
+    pass_error: bool,
+    /// The synchronization state of the CSI transmitter changed.
+    sync: bool,
+    /// The forwarded RX ports lost synchronization.
+    sync_error: bool,
+}
+
+impl CsiTxIrq {
+    const fn from_csi_tx_isr(value: u32) -> Self {
+        Self {
+            pass: value & (1 << ti954::IS_CSI_PASS) != 0,
+            pass_error: value & (1 << ti954::IS_CSI_PASS_ERR_OR) != 0,
+            sync: value & (1 << ti954::IS_CSI_SYNC) != 0,
+            sync_error: value & (1 << ti954::IS_CSI_SYNC_ERR_OR) != 0,
+        }
+    }
+
+    const fn has_error(&self) -> bool {
+        self.pass_error || self.sync_error
+    }
+}
+
+kernel::static_assert!(matches!(
+    CsiTxIrq::from_csi_tx_isr(0b0101),
+    CsiTxIrq {
+        pass: true,
+        pass_error: false,
+        sync: true,
+        sync_error: false
+    }
+));
+kernel::static_assert!(matches!(
+    CsiTxIrq::from_csi_tx_isr(0b1010),
+    CsiTxIrq {
+        pass: false,
+        pass_error: true,
+        sync: false,
+        sync_error: true
+    }
+));
+kernel::static_assert!(!CsiTxIrq::from_csi_tx_isr(0xf5).has_error());
+kernel::static_assert!(CsiTxIrq::from_csi_tx_isr(0x08).has_error());
+
+/// Encode `REG_IND_ACC_CTL` to access the indirect register page `page`.
+const fn ind_acc_ctl(page: u32, read: bool) -> u32 {
+    (page << ti954::IA_SEL) | ((read as u32) << ti954::IA_READ)
+}
+
+/// Encode `REG_IND_ACC_CTL` for reading consecutive registers of page `page`.
+const fn ind_acc_ctl_auto_inc(page: u32) -> u32 {
+    ind_acc_ctl(page, true) | (1 << ti954::IA_AUTO_INC)
+}
+
+/// Whether `len` registers starting at `start` fit into one 256 register indirect page.
+const fn ia_block_fits(start: u32, len: usize) -> bool {
+    match (start as usize).checked_add(len) {
+        Some(end) => end <= 0x100,
+        None => false,
+    }
+}
+
+/// Whether `REG_IND_ACC_CTL` has to be written to get to `ctl`, given the `selected` value.
+const fn needs_ia_select(selected: Option<u32>, ctl: u32) -> bool {
+    match selected {
+        Some(selected) => selected != ctl,
+        None => true,
+    }
+}
+
+kernel::static_assert!(ind_acc_ctl(ti954::REG_IA_PATTERN_GEN_PAGE_BLOCK_SELECT, false) == 0x00);
+kernel::static_assert!(ind_acc_ctl(ti954::REG_IA_PATTERN_GEN_PAGE_BLOCK_SELECT, true) == 0x01);
+kernel::static_assert!(ind_acc_ctl(1, false) == 0x04);
+kernel::static_assert!(ind_acc_ctl_auto_inc(ti954::REG_IA_PATTERN_GEN_PAGE_BLOCK_SELECT) == 0x03);
+kernel::static_assert!(ind_acc_ctl_auto_inc(1) == 0x07);
+kernel::static_assert!(needs_ia_select(
+    Some(ind_acc_ctl(1, true)),
+    ind_acc_ctl_auto_inc(1)
+));
+kernel::static_assert!(ia_block_fits(0x00, 0x100));
+kernel::static_assert!(ia_block_fits(0xf0, 0x10));
+kernel::static_assert!(!ia_block_fits(0xf0, 0x11));
+kernel::static_assert!(!ia_block_fits(0x100, 1));
+kernel::static_assert!(!ia_block_fits(0x01, usize::MAX));
+kernel::static_assert!(needs_ia_select(None, 0x00));
+kernel::static_assert!(!needs_ia_select(Some(0x00), 0x00));
+kernel::static_assert!(needs_ia_select(Some(0x00), 0x01));
+kernel::static_assert!(needs_ia_select(Some(0x04), 0x00));
+
+/// Check that the clock divider value `val` fits the `REG_CLKOUT_CTRL0`/`REG_CLKOUT_CTRL1`
+/// field from bit `lsb` up to, but not including, bit `end`.
+const fn clkout_div_fits(val: u32, lsb: usize, end: usize) -> bool {
+    val < 1 << (end - lsb)
+}
+
+kernel::static_assert!(clkout_div_fits(0x1f, ti953::DIV_M_VAL, ti953::HS_CLK_DIV));
+kernel::static_assert!(!clkout_div_fits(0x20, ti953::DIV_M_VAL, ti953::HS_CLK_DIV));
+kernel::static_assert!(clkout_div_fits(0xff, ti953::DIV_N_VAL, 8));
+kernel::static_assert!(!clkout_div_fits(0x100, ti953::DIV_N_VAL, 8));
+
+/// Check that `addr` can be used as serializer, slave or alias address, which is a 7-bit I2C
+/// address outside of the reserved ranges.
+const fn i2c_addr_is_valid(addr: u32) -> bool {
+    matches!(addr, 0x08..=0x77)
+}
+
+kernel::static_assert!(i2c_addr_is_valid(0x18));
+kernel::static_assert!(i2c_addr_is_valid(0x77));
+kernel::static_assert!(!i2c_addr_is_valid(0x00));
+kernel::static_assert!(!i2c_addr_is_valid(0x78));
+kernel::static_assert!(!i2c_addr_is_valid(0x118));
+
+/// Default alias of the serializer in slot `index` of a deserializer at address `own`.
+///
+/// Serializers get consecutive aliases from 0x18 on, skipping the deserializer's own address.
+const fn default_ser_alias(own: u32, index: usize) -> u32 {
+    let alias = 0x18 + index as u32;
+    if alias == own {
+        alias + NUM_SERIALIZER as u32
+    } else {
+        alias
+    }
+}
+
+kernel::static_assert!(default_ser_alias(0x30, 0) == 0x18);
+kernel::static_assert!(default_ser_alias(0x30, 1) == 0x19);
+kernel::static_assert!(default_ser_alias(0x18, 0) == 0x1a);
+kernel::static_assert!(default_ser_alias(0x19, 1) == 0x1b);
+
+/// Index of the serializer connected to `rx_channel`, which is also its slot in
+/// `Ds90ub954Data::serializers`.
+const fn serializer_index(rx_channel: u32) -> Option<usize> {
+    match rx_channel {
+        0 | 1 => Some(rx_channel as usize),
+        _ => None,
+    }
+}
+
+kernel::static_assert!(matches!(serializer_index(0), Some(0)));
+kernel::static_assert!(matches!(serializer_index(1), Some(1)));
+kernel::static_assert!(serializer_index(RxPort::Both as u32).is_none());
+kernel::static_assert!(serializer_index(3).is_none());
+
+/// Reason for not configuring a child of the `serializers` device tree node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SerializerSkip {
+    /// The node has e.g. `status = "disabled"`.
+    Disabled,
+    InvalidRxChannel,
+    DuplicateRxChannel,
+}
+
+/// Slot in `Ds90ub954Data::serializers` of a serializer node connected to `rx_channel`, given
+/// whether the node is `available` and which slots are already `used`.
+const fn serializer_slot(
+    available: bool,
+    rx_channel: u32,
+    used: &[bool; NUM_SERIALIZER],
+) -> Result<usize, SerializerSkip> {
+    if !available {
+        return Err(SerializerSkip::Disabled);
+    }
+    match serializer_index(rx_channel) {
+        None => Err(SerializerSkip::InvalidRxChannel),
+        Some(i) if used[i] => Err(SerializerSkip::DuplicateRxChannel),
+        Some(i) => Ok(i),
+    }
+}
+
+kernel::static_assert!(matches!(serializer_slot(true, 1, &[true, false]), Ok(1)));
+// A disabled node is skipped even though its rx-channel is valid and free.
+kernel::static_assert!(matches!(
+    serializer_slot(false, 0, &[false, false]),
+    Err(SerializerSkip::Disabled)
+));
+kernel::static_assert!(matches!(
+    serializer_slot(true, 2, &[false, false]),
+    Err(SerializerSkip::InvalidRxChannel)
+));
+kernel::static_assert!(matches!(
+    serializer_slot(true, 0, &[true, false]),
+    Err(SerializerSkip::DuplicateRxChannel)
+));
+
+/// Value of the indirect `REG_IA_PGEN_CTL` register enabling or disabling the pattern generator.
+const fn pgen_ctl(enable: bool) -> u32 {
+    (if enable { 1 } else { 0 }) << ti954::PGEB_ENABLE
+}
+
+kernel::static_assert!(pgen_ctl(true) == 0x01);
+kernel::static_assert!(pgen_ctl(false) == 0x00);
+
+/// A table of `(register, value)` pairs with 8-bit registers and values, setting each register
+/// once.
+struct RegTable<const N: usize>([(u32, u32); N]);
+
+impl<const N: usize> RegTable<N> {
+    /// Create a table from `pairs`, panicking if they are invalid.
+    ///
+    /// Tables are meant to be constants, for which this fails the build instead.
+    const fn new(pairs: &[(u32, u32); N]) -> Self {
+        assert!(reg_table_is_valid(pairs), "invalid register table");
+        Self(*pairs)
+    }
+}
+
+/// Check that a table of `(register, value)` pairs only holds 8-bit registers and values, and
+/// sets each register once.
+const fn reg_table_is_valid(table: &[(u32, u32)]) -> bool {
+    let mut i = 0;
+    while i < table.len() {
+        if table[i].0 > 0xff || table[i].1 > 0xff {
+            return false;
+        }
+        let mut j = i + 1;
+        while j < table.len() {
+            if table[i].0 == table[j].0 {
+                return false;
+            }
+            j += 1;
+        }
+        i += 1;
+    }
+    true
+}
+
+kernel::static_assert!(reg_table_is_valid(&[]));
+kernel::static_assert!(!reg_table_is_valid(&[(0x01, 0x100)]));
+kernel::static_assert!(!reg_table_is_valid(&[
+    (0x01, 0x00),
+    (0x02, 0x00),
+    (0x01, 0x01)
+]));
+
+/// Expand `(indirect register, value)` pairs to the direct writes of `addr_reg` and `data_reg`
+/// performing them.
+const fn indirect_write_sequence<const N: usize>(
+    addr_reg: u32,
+    data_reg: u32,
+    regs: &RegTable<N>,
+) -> [[(u32, u32); 2]; N] {
+    let regs = &regs.0;
+    let mut seq = [[(0, 0); 2]; N];
+    let mut i = 0;
+    while i < N {
+        seq[i] = [(addr_reg, regs[i].0), (data_reg, regs[i].1)];
+        i += 1;
+    }
+    seq
+}
+
+kernel::static_assert!(matches!(
+    indirect_write_sequence(0xb1, 0xb2, &RegTable::new(&[(0x01, 0x35), (0x02, 0x2b)])),
+    [[(0xb1, 0x01), (0xb2, 0x35)], [(0xb1, 0x02), (0xb2, 0x2b)]]
+));
+
+/// Decode `REG_CSI_VC_MAP`, returning the VC each of the four incoming VCs is mapped to.
+const fn decode_vc_map(map: u8) -> [u8; 4] {
+    [
+        map & 0b11,
+        (map >> 2) & 0b11,
+        (map >> 4) & 0b11,
+        (map >> 6) & 0b11,
+    ]
+}
+
+/// Whether a `REG_CSI_VC_MAP` value keeps the incoming VCs apart.
+///
+/// Mapping two incoming VCs to the same VC merges their streams, which can't be told apart on
+/// the CSI-2 output anymore.
+const fn vc_map_is_valid(map: u8) -> bool {
+    let vcs = decode_vc_map(map);
+    let mut seen = 0u8;
+    let mut i = 0;
+    while i < vcs.len() {
+        if seen & (1 << vcs[i]) != 0 {
+            return false;
+        }
+        seen |= 1 << vcs[i];
+        i += 1;
+    }
+    true
+}
+
+/// Whether two RX ports using the `REG_CSI_VC_MAP` values `a` and `b` forward VC 0, which most
+/// sensors send on, to the same CSI output VC.
+const fn vc_maps_collide(a: u8, b: u8) -> bool {
+    decode_vc_map(a)[0] == decode_vc_map(b)[0]
+}
+
+kernel::static_assert!(matches!(decode_vc_map(0xe4), [0, 1, 2, 3]));
+kernel::static_assert!(matches!(decode_vc_map(0x1b), [3, 2, 1, 0]));
+kernel::static_assert!(vc_map_is_valid(0xe4));
+kernel::static_assert!(vc_map_is_valid(0x4e));
+kernel::static_assert!(!vc_map_is_valid(0x00));
+kernel::static_assert!(!vc_map_is_valid(0xe5));
+kernel::static_assert!(vc_maps_collide(0xe4, 0xe4));
+kernel::static_assert!(!vc_maps_collide(0xe4, 0xe1));
+kernel::static_assert!(vc_maps_collide(0x1b, 0xe7));
+
+/// Find the first i2c address in `aliases` that is used twice or equals the deserializer
+/// address `own`.
+///
+/// The deserializer answers on all of these addresses on the local bus, so any overlap makes
+/// accesses to one of the devices go to another.
+const fn first_address_conflict(own: u32, aliases: &[u32]) -> Option<u32> {
+    let mut i = 0;
+    while i < aliases.len() {
+        if aliases[i] == own {
+            return Some(own);
+        }
+        let mut j = i + 1;
+        while j < aliases.len() {
+            if aliases[i] == aliases[j] {
+                return Some(aliases[i]);
+            }
+            j += 1;
+        }
+        i += 1;
+    }
+    None
+}
+
+kernel::static_assert!(first_address_conflict(0x30, &[]).is_none());
+kernel::static_assert!(first_address_conflict(0x30, &[0x18, 0x19, 0x40, 0x41]).is_none());
+kernel::static_assert!(matches!(
+    first_address_conflict(0x30, &[0x18, 0x30]),
+    Some(0x30)
+));
+kernel::static_assert!(matches!(
+    first_address_conflict(0x30, &[0x18, 0x40, 0x18]),
+    Some(0x18)
+));
+
+/// Check that at least one of the `serializers` slots is in use.
+///
+/// Returns [`ENODEV`] otherwise, as there is nothing to forward to the CSI output.
+fn require_serializer<T>(serializers: &[Option<T>]) -> Result<()> {
+    if serializers.iter().any(Option::is_some) {
+        Ok(())
+    } else {
+        Err(ENODEV)
+    }
+}
+
+/// Power state of the deserializer, controlled through the PDB pin.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PowerState {
+    Off,
+    On,
+}
+
+impl PowerState {
+    /// Whether going to `target` changes anything.
+    const fn needs_transition(self, target: PowerState) -> bool {
+        !matches!(
+            (self, target),
+            (PowerState::Off, PowerState::Off) | (PowerState::On, PowerState::On)
+        )
+    }
+
+    /// Value of the active-high PDB pin for this state.
+    const fn to_pdb_value(self) -> i32 {
+        match self {
+            PowerState::Off => 0,
+            PowerState::On => 1,
+        }
+    }
+}
+
+kernel::static_assert!(PowerState::Off.needs_transition(PowerState::On));
+kernel::static_assert!(PowerState::On.needs_transition(PowerState::Off));
+kernel::static_assert!(!PowerState::On.needs_transition(PowerState::On));
+kernel::static_assert!(!PowerState::Off.needs_transition(PowerState::Off));
+
+/// Update a `REG_GENERAL_CFG` value for forcing REFCLK detection.
+const fn general_cfg_force_refclk(value: u32, force: bool) -> u32 {
+    if force {
+        value | (1 << ti954::FORCE_REFCLK_DET)
+    } else {
+        value & !(1 << ti954::FORCE_REFCLK_DET)
+    }
+}
+
+kernel::static_assert!(general_cfg_force_refclk(0x1e, true) == 0x1f);
+kernel::static_assert!(general_cfg_force_refclk(0x1f, false) == 0x1e);
+kernel::static_assert!(general_cfg_force_refclk(0x01, true) == 0x01);
+
+/// Round robin forwarding weight of CSI port 0, the 2-bit `CSI0_RR_RWD` field.
+///
+/// A weight of zero disables round robin forwarding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct RrWeight(u8);
+
+impl RrWeight {
+    const MASK: u32 = 0b11 << ti954::CSI0_RR_RWD;
+
+    const fn new(weight: u32) -> Option<Self> {
+        if weight > 0b11 {
+            return None;
+        }
+        Some(Self(weight as u8))
+    }
+
+    /// Replace the `CSI0_RR_RWD` field of a `REG_FWD_CTL2` value.
+    const fn apply_to_fwd_ctl2(self, value: u32) -> u32 {
+        (value & !Self::MASK) | ((self.0 as u32) << ti954::CSI0_RR_RWD)
+    }
+}
+
+kernel::static_assert!(RrWeight::new(4).is_none());
+kernel::static_assert!(matches!(RrWeight::new(3), Some(RrWeight(3))));
+kernel::static_assert!(RrWeight(0).apply_to_fwd_ctl2(0x83) == 0x80);
+kernel::static_assert!(RrWeight(2).apply_to_fwd_ctl2(0x41) == 0x42);
+kernel::static_assert!(RrWeight(3).apply_to_fwd_ctl2(0x00) == 0x03);
+
+/// Whether a serializer with `ser_lanes` CSI lanes fits the deserializer's `des_lanes` outputs.
+///
+/// The deserializer can't forward more lanes than it outputs.
+const fn csi_lane_counts_compatible(des_lanes: u32, ser_lanes: u32) -> bool {
+    ser_lanes <= des_lanes
+}
+
+kernel::static_assert!(csi_lane_counts_compatible(4, 4));
+kernel::static_assert!(csi_lane_counts_compatible(4, 2));
+kernel::static_assert!(!csi_lane_counts_compatible(2, 4));
+kernel::static_assert!(!csi_lane_counts_compatible(1, 2));
+
+/// Encode `REG_FV_MIN_TIME`.
+const fn fv_min_time_reg(ticks: u8) -> u32 {
+    (ticks as u32) << ti954::FRAME_VALID_MIN
+}
+
+kernel::static_assert!(fv_min_time_reg(0) == 0x00);
+kernel::static_assert!(fv_min_time_reg(0x80) == 0x80);
+kernel::static_assert!(fv_min_time_reg(0xff) == 0xff);
+
+/// Back channel watchdog timeout, the 7-bit `BCC_WATCHDOG_TIMER` field in units of 2 ms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct BccWdTimeout(u8);
+
+impl BccWdTimeout {
+    /// Timeout after reset, 254 ms.
+    const DEFAULT: Self = Self(0x7f);
+
+    /// Timeout of at least `ms` milliseconds, rounded up to the 2 ms resolution.
+    ///
+    /// A timeout of zero is not allowed by the hardware.
+    const fn from_ms(ms: u32) -> Option<Self> {
+        let units = ms.div_ceil(2);
+        if units == 0 || units > 0x7f {
+            return None;
+        }
+        Some(Self(units as u8))
+    }
+
+    /// Encode `REG_BCC_WD_CTL`.
+    const fn to_bcc_wd_ctl(self, disable: bool) -> u32 {
+        ((self.0 as u32) << ti954::BCC_WATCHDOG_TIMER)
+            | ((disable as u32) << ti954::BCC_WATCHDOG_TIMER_DISABLE)
+    }
+}
+
+kernel::static_assert!(BccWdTimeout::from_ms(0).is_none());
+kernel::static_assert!(BccWdTimeout::from_ms(255).is_none());
+kernel::static_assert!(matches!(BccWdTimeout::from_ms(1), Some(BccWdTimeout(1))));
+kernel::static_assert!(matches!(
+    BccWdTimeout::from_ms(254),
+    Some(BccWdTimeout(0x7f))
+));
+kernel::static_assert!(BccWdTimeout::DEFAULT.to_bcc_wd_ctl(false) == 0xfe);
+kernel::static_assert!(BccWdTimeout(0x10).to_bcc_wd_ctl(false) == 0x20);
+kernel::static_assert!(BccWdTimeout(0x10).to_bcc_wd_ctl(true) == 0x21);
+
+/// Update a `REG_FWD_CTL2` value for enabling or disabling CSI replication.
+const fn fwd_ctl2_replicate(value: u32, enable: bool) -> u32 {
+    if !enable {
+        return value & !(1 << ti954::CSI_REPLICATE);
+    }
+
+    let mut value = value | (1 << ti954::CSI_REPLICATE);
+    if value & (0b11 << ti954::CSI0_SYNC_FWD) == 0 {
+        value |= 1 << ti954::CSI0_RR_RWD;
+    }
+    value
+}
+
+kernel::static_assert!(fwd_ctl2_replicate(0x00, true) == 0x81);
+kernel::static_assert!(fwd_ctl2_replicate(0x04, true) == 0x84);
+kernel::static_assert!(fwd_ctl2_replicate(0x81, false) == 0x01);
+kernel::static_assert!(fwd_ctl2_replicate(0x40, false) == 0x40);
+
+/// `REG_GPIO_PD_CTL` bit disabling the pull-down of `gpio`, if the GPIO exists.
+const fn gpio_pd_dis_bit(gpio: u8) -> Option<u32> {
+    let shift = match gpio {
+        0 => ti954::GPIO0_PD_DIS,
+        1 => ti954::GPIO1_PD_DIS,
+        2 => ti954::GPIO2_PD_DIS,
+        3 => ti954::GPIO3_PD_DIS,
+        4 => ti954::GPIO4_PD_DIS,
+        5 => ti954::GPIO5_PD_DIS,
+        6 => ti954::GPIO6_PD_DIS,
+        _ => return None,
+    };
+    Some(1 << shift)
+}
+
+kernel::static_assert!(matches!(gpio_pd_dis_bit(0), Some(0x01)));
+kernel::static_assert!(matches!(gpio_pd_dis_bit(1), Some(0x02)));
+kernel::static_assert!(matches!(gpio_pd_dis_bit(2), Some(0x04)));
+kernel::static_assert!(matches!(gpio_pd_dis_bit(3), Some(0x08)));
+kernel::static_assert!(matches!(gpio_pd_dis_bit(4), Some(0x10)));
+kernel::static_assert!(matches!(gpio_pd_dis_bit(5), Some(0x20)));
+kernel::static_assert!(matches!(gpio_pd_dis_bit(6), Some(0x40)));
+kernel::static_assert!(gpio_pd_dis_bit(7).is_none());
+
+/// Decode `LOCK_STS` of `REG_RX_PORT_STS1`.
+const fn rx_port_locked(sts1: u32) -> bool {
+    sts1 & (1 << ti954::LOCK_STS) != 0
+}
+
+kernel::static_assert!(rx_port_locked(0x01));
+kernel::static_assert!(rx_port_locked(0x03));
+kernel::static_assert!(!rx_port_locked(0x00));
+kernel::static_assert!(!rx_port_locked(0xfe));
+
+/// Decode `LOCK_STS` and `PORT_PASS` of `REG_RX_PORT_STS1`, both must be set.
+const fn rx_port_locked_and_passing(sts1: u32) -> bool {
+    let mask = (1 << ti954::LOCK_STS) | (1 << ti954::PORT_PASS);
+    sts1 & mask == mask
+}
+
+kernel::static_assert!(rx_port_locked_and_passing(0x03));
+kernel::static_assert!(rx_port_locked_and_passing(0xff));
+kernel::static_assert!(!rx_port_locked_and_passing(0x01));
+kernel::static_assert!(!rx_port_locked_and_passing(0x02));
+
+/// Encode `REG_LINK_ERROR_COUNT` with counting enabled, the threshold is limited to 4 bits.
+const fn link_error_count(threshold: u8) -> Option<u32> {
+    if threshold >= 1 << (ti954::LINK_ERR_COUNT_EN - ti954::LINK_ERR_THRESH) {
+        return None;
+    }
+    Some(((threshold as u32) << ti954::LINK_ERR_THRESH) | (1 << ti954::LINK_ERR_COUNT_EN))
+}
+
+/// `REG_AEQ_CTL2` values setting and clearing the AEQ restart bit, keeping the other settings
+/// of `value`.
+const fn aeq_ctl2_restart_pulse(value: u32) -> [u32; 2] {
+    let restart = 1 << ti954::AEQ_RESTART;
+    [value | restart, value & !restart]
+}
+
+/// Update the `ADAPTIVE_EQ_RELOCK_TIME` field of the `REG_AEQ_CTL2` value `value` to `ticks`.
+///
+/// Returns `None` if `ticks` doesn't fit the 3-bit field.
+const fn aeq_ctl2_relock_time(value: u32, ticks: u8) -> Option<u32> {
+    if ticks > 0b111 {
+        return None;
+    }
+    let mask = 0b111 << ti954::ADAPTIVE_EQ_RELOCK_TIME;
+    Some((value & !mask) | ((ticks as u32) << ti954::ADAPTIVE_EQ_RELOCK_TIME))
+}
+
+kernel::static_assert!(matches!(link_error_count(0), Some(0x10)));
+kernel::static_assert!(matches!(link_error_count(3), Some(0x13)));
+kernel::static_assert!(matches!(link_error_count(15), Some(0x1f)));
+kernel::static_assert!(link_error_count(16).is_none());
+kernel::static_assert!(matches!(aeq_ctl2_restart_pulse(0x00), [0x08, 0x00]));
+kernel::static_assert!(matches!(aeq_ctl2_restart_pulse(0xa4), [0xac, 0xa4]));
+kernel::static_assert!(matches!(aeq_ctl2_restart_pulse(0xac), [0xac, 0xa4]));
+kernel::static_assert!(matches!(aeq_ctl2_relock_time(0x00, 0), Some(0x00)));
+kernel::static_assert!(matches!(aeq_ctl2_relock_time(0x00, 7), Some(0xe0)));
+kernel::static_assert!(matches!(aeq_ctl2_relock_time(0xff, 2), Some(0x5f)));
+kernel::static_assert!(aeq_ctl2_relock_time(0x00, 8).is_none());
+
+/// Register of mailbox `index`, if it exists.
+const fn mailbox_reg(index: u8) -> Option<u32> {
+    match index {
+        0 => Some(ti954::REG_MAILBOX_1),
+        1 => Some(ti954::REG_MAILBOX_2),
+        _ => None,
+    }
+}
+
+kernel::static_assert!(matches!(mailbox_reg(0), Some(0x78)));
+kernel::static_assert!(matches!(mailbox_reg(1), Some(0x79)));
+kernel::static_assert!(mailbox_reg(2).is_none());
+
+struct Ds90ub954ParseDtReturn {
+    pass_gpio: Option<gpio::Desc>,
+    lock_gpio: Option<gpio::Desc>,
+    pdb_gpio: Option<gpio::Desc>,
+    csi_lane_count: u32,
+    csi_lane_speed: CsiLaneSpeed,
+    test_pattern: bool,
+    continuous_clock: bool,
+    output_mode: OutputMode,
+    refclk_mhz: u8,
+    link_error_threshold: Option<u8>,
+    gpio_pulldown_disable: u8,
+    force_refclk_detect: bool,
+    round_robin_weight: Option<RrWeight>,
+    bcc_watchdog_timeout: Option<BccWdTimeout>,
+    bcc_watchdog_disable: bool,
+    fv_min_time: Option<u8>,
+    csi_replicate: bool,
+    csi_lane_partition: LanePartition,
+}
+fn ds90ub954_parse_dt(dev: &kernel::device::Device) -> Result<Ds90ub954ParseDtReturn> {
+    let try_get_gpio = |con_id: &'static CStr, flags: gpio::Flags| -> Result<Option<gpio::Desc>> {
+        match gpio::Desc::get(dev, con_id, flags) {
+            Ok(gpio) => Ok(Some(gpio)),
+            Err(e) if e == EPROBE_DEFER => {
+                dev_err!(dev, "{con_id}-gpio read failed: (EPROBE_DEFER)\n");
+                Err(e)
+            }
+            Err(_) => {
+                dev_info!(dev, "{con_id}-gpio not found, ignoring\n");
+                Ok(None)
+            }
+        }
+    };
+    let mut pass_gpio = try_get_gpio(c_str!("pass"), gpio::Flags::In)?;
+    let mut lock_gpio = try_get_gpio(c_str!("lock"), gpio::Flags::In)?;
+    // both toggle while the links are trained
+    for (con_id, gpio) in [("pass", &mut pass_gpio), ("lock", &mut lock_gpio)] {
+        if let Some(gpio) = gpio {
+            if let Err(e) = gpio.set_debounce(STATUS_GPIO_DEBOUNCE_US) {
+                dev_info!(dev, "{con_id}-gpio can't be debounced ({e}), ignoring\n");
+            }
+        }
+    }
+    let pdb_gpio = try_get_gpio(c_str!("pdb"), gpio::Flags::OutLow)?;
+
+    let fwnode = dev.as_fwnode();
+
+    let csi_lane_count = fwnode
+        .property_read::<u32>(c_str!("csi-lane-count"), None)
+        .unwrap_or_else(|_| {
+            dev_info!(
+                dev,
+                "csi-lane-count property not found, set to default value\n"
+            );
+            4
+        });
+    dev_info!(dev, "csi-lane-count: {csi_lane_count}\n");
+
+    let csi_lane_speed = dev
+        .property_read_enum(c_str!("csi-lane-speed"))
+        .unwrap_or_else(|_| {
+            if fwnode.property_present(c_str!("csi-lane-speed")) {
+                dev_err!(dev, "invalid value for csi-lane-speed, using default\n");
+            } else {
+                dev_info!(
+                    dev,
+                    "csi-lane-speed property not found, set to default value\n"
+                );
+            }
+            CsiLaneSpeed::Mbps1600
+        });
+    dev_info!(dev, "csi-lane-speed: {csi_lane_speed:?}\n");
+
+    let test_pattern = fwnode.property_read_bool(c_str!("test-pattern"));
+    if test_pattern {
+        dev_info!(dev, "test-pattern enabled\n");
+    } else {
+        dev_info!(dev, "test-pattern disabled\n");
+    }
+
+    let continuous_clock = fwnode.property_read_bool(c_str!("continuous-clock"));
     if continuous_clock {
         dev_info!(dev, "continuous clock enabled\n");
     } else {
         dev_info!(dev, "discontinuous clock used\n");
     }
 
+    let output_mode = if fwnode
+        .property_match_string(c_str!("output-mode"), c_str!("always-on"))
+        .is_ok()
+    {
+        OutputMode::AlwaysOn
+    } else {
+        OutputMode::Auto
+    };
+    dev_info!(dev, "output-mode: {output_mode:?}\n");
+
+    let refclk_hz = fwnode
+        .property_read::<u32>(c_str!("refclk-hz"), None)
+        .unwrap_or_else(|_| {
+            dev_info!(dev, "refclk-hz property not found, set to default value\n");
+            DEFAULT_REFCLK_HZ
+        });
+    let Some(refclk_mhz) = refclk_hz_to_mhz(refclk_hz) else {
+        dev_err!(dev, "refclk-hz out of range: {refclk_hz}\n");
+        return Err(EINVAL);
+    };
+    dev_info!(dev, "refclk: {refclk_mhz} MHz\n");
+
+    let link_error_threshold =
+        match fwnode.property_read::<u32>(c_str!("link-error-threshold"), None) {
+            Err(_) => None,
+            Ok(v @ 0..=15) => Some(v as u8),
+            Ok(v) => {
+                dev_err!(
+                    dev,
+                    "invalid value ({v}) for link-error-threshold, ignoring\n"
+                );
+                None
+            }
+        };
+
+    // bitmask of the GPIOs listed in gpio-pulldown-disable
+    let mut gpio_pulldown_disable = 0;
+    if fwnode.property_present(c_str!("gpio-pulldown-disable")) {
+        let gpios = fwnode
+            .property_count_elem::<u32>(c_str!("gpio-pulldown-disable"))
+            .and_then(|len| {
+                fwnode.property_read_array_vec::<u32>(c_str!("gpio-pulldown-disable"), len)
+            })?;
+        for &gpio in gpios.iter() {
+            let Some(bit) = u8::try_from(gpio).ok().and_then(gpio_pd_dis_bit) else {
+                dev_err!(dev, "invalid gpio ({gpio}) in gpio-pulldown-disable\n");
+                return Err(EINVAL);
+            };
+            gpio_pulldown_disable |= bit as u8;
+        }
+    }
+    dev_info!(
+        dev,
+        "gpio pull-down disabled: 0b{gpio_pulldown_disable:07b}\n"
+    );
+
+    let force_refclk_detect = fwnode.property_read_bool(c_str!("force-refclk-detect"));
+    if force_refclk_detect {
+        dev_info!(dev, "force-refclk-detect enabled\n");
+    }
+
+    let round_robin_weight = match fwnode.property_read::<u32>(c_str!("round-robin-weight"), None) {
+        Err(_) => None,
+        Ok(v) => match RrWeight::new(v) {
+            Some(weight) => Some(weight),
+            None => {
+                dev_err!(
+                    dev,
+                    "invalid value ({v}) for round-robin-weight, ignoring\n"
+                );
+                None
+            }
+        },
+    };
+
+    let bcc_watchdog_timeout =
+        match fwnode.property_read::<u32>(c_str!("bcc-watchdog-timeout-ms"), None) {
+            Err(_) => None,
+            Ok(v) => match BccWdTimeout::from_ms(v) {
+                Some(timeout) => Some(timeout),
+                None => {
+                    dev_err!(
+                        dev,
+                        "invalid value ({v}) for bcc-watchdog-timeout-ms, ignoring\n"
+                    );
+                    None
+                }
+            },
+        };
+
+    let fv_min_time = match fwnode.property_read::<u32>(c_str!("fv-min-time"), None) {
+        Err(_) => None,
+        Ok(v) => match u8::try_from(v) {
+            Ok(ticks) => Some(ticks),
+            Err(_) => {
+                dev_err!(dev, "invalid value ({v}) for fv-min-time, ignoring\n");
+                None
+            }
+        },
+    };
+
+    let bcc_watchdog_disable = fwnode.property_read_bool(c_str!("bcc-watchdog-disable"));
+    if bcc_watchdog_disable {
+        dev_info!(dev, "bcc watchdog disabled\n");
+    }
+    let csi_replicate = fwnode.property_read_bool(c_str!("csi-replicate"));
+    if csi_replicate {
+        dev_info!(dev, "csi-replicate enabled\n");
+    }
+    let csi_lane_partition = if fwnode.property_read_bool(c_str!("csi-split-lanes")) {
+        LanePartition::Split
+    } else {
+        LanePartition::Single
+    };
+    dev_info!(dev, "csi lane partition: {csi_lane_partition:?}\n");
+    // splitting fixes the number of lanes of each port
+    let csi_lane_count = if csi_lane_partition == LanePartition::Split
+        && csi_lane_count != csi_lane_partition.lane_count()
+    {
+        dev_warn!(
+            dev,
+            "csi-split-lanes uses {} lanes per port, ignoring csi-lane-count\n",
+            csi_lane_partition.lane_count()
+        );
+        csi_lane_partition.lane_count()
+    } else {
+        csi_lane_count
+    };
+
     Ok(Ds90ub954ParseDtReturn {
         pass_gpio,
         lock_gpio,
@@ -1709,29 +3123,50 @@ fn ds90ub954_parse_dt(dev: &kernel::device::Device) -> Result<Ds90ub954ParseDtRe
         csi_lane_speed,
         test_pattern,
         continuous_clock,
+        output_mode,
+        refclk_mhz,
+        link_error_threshold,
+        gpio_pulldown_disable,
+        force_refclk_detect,
+        round_robin_weight,
+        bcc_watchdog_timeout,
+        bcc_watchdog_disable,
+        fv_min_time,
+        csi_replicate,
+        csi_lane_partition,
     })
 }
 
 struct Ds90ub953 {
-    i2c_client: i2c::Client,
+    // The regmap accesses the serializer through its dummy client, so it is dropped first.
     regmap: regmap::Regmap,
+    i2c_client: i2c::DummyClient,
     rx_channel: RxPort,
     test_pattern: bool,
     i2c_address: u32,
     csi_lane_count: u32,
-    i2c_slave: ArrayVec<NUM_ALIAS, u64>, // array with the i2c slave addresses
-    i2c_alias: ArrayVec<NUM_ALIAS, u64>, // array with the i2c alias addresses
+    // (slave address, alias address) pairs of the devices behind the serializer
+    i2c_aliases: ArrayVec<NUM_ALIAS, (u32, u32)>,
     continuous_clock: bool,
-    i2c_pass_through_all: bool,
+    csi_lane_polarity: CsiLanePolarity,
+    i2c_forwarding: I2cFwd,
+    csi_forward_options: CsiFwdOpts,
+    input_mode: InputMode,
 
     gpio: [Ds90ub953GpioConfig; 4],
 
     // reference output clock control parameters
-    hs_clk_div: u32,
+    hs_clk_div: HsClkDiv,
     div_m_val: u32,
     div_n_val: u32,
 
-    virtual_channel_map: u32,
+    virtual_channel_map: u8,
+
+    pass_control: PassControl,
+
+    aeq_relock_time: Option<u8>,
+
+    embedded_dtype: Option<u8>,
 }
 #[derive(Debug, Clone, Copy)]
 struct Ds90ub953GpioConfig {
@@ -1748,7 +3183,7 @@ struct Ds90ub953GpioConfig {
         return Err(ENOENT);
     };
 
-    for (i, serializer) in serializers_node.children().enumerate().take(NUM_SERIALIZER) {
+    for serializer in serializers_node.children() {
         let get_u32 = |prop, default| {
             let val = serializer
                 .property_read::<u32>(prop, None)
@@ -1760,7 +3195,33 @@ struct Ds90ub953GpioConfig {
             val
         };
 
-        let rx_channel = RxPort::from(get_u32(c_str!("rx-channel"), 0), dev);
+        let rx_channel = get_u32(c_str!("rx-channel"), 0);
+        let used = core::array::from_fn(|i| res[i].is_some());
+        let i = match serializer_slot(serializer.is_available(), rx_channel, &used) {
+            Ok(i) => i,
+            Err(SerializerSkip::Disabled) => {
+                dev_info!(
+                    dev,
+                    "serializer on rx-channel {rx_channel} is disabled, skipping\n"
+                );
+                continue;
+            }
+            Err(SerializerSkip::InvalidRxChannel) => {
+                dev_err!(
+                    dev,
+                    "invalid rx-channel ({rx_channel}) for serializer, skipping\n"
+                );
+                continue;
+            }
+            Err(SerializerSkip::DuplicateRxChannel) => {
+                dev_err!(
+                    dev,
+                    "duplicate rx-channel ({rx_channel}) for serializer, skipping\n"
+                );
+                continue;
+            }
+        };
+        let rx_channel = RxPort::from(rx_channel, dev);
 
         let test_pattern = serializer.property_read_bool(c_str!("test-pattern"));
         if test_pattern {
@@ -1790,46 +3251,44 @@ struct Ds90ub953GpioConfig {
             },
         ];
 
-        let hs_clk_div_default = 0b010; // div by 4
-        let hs_clk_div = match serializer.property_read::<u32>(c_str!("hs-clk-div"), None) {
-            Ok(1) => 0b000,
-            Ok(2) => 0b001,
-            Ok(4) => 0b010,
-            Ok(8) => 0b011,
-            Ok(16) => 0b100,
-            Ok(v) => {
-                dev_err!(dev, "invalid value ({v}) for hs-clk-div, using default\n");
-                hs_clk_div_default
-            }
-            Err(_) => {
-                dev_info!(dev, "hs-clk-div property not found, set to default value\n");
-                hs_clk_div_default
+        let hs_clk_div = serializer
+            .property_read_enum(c_str!("hs-clk-div"))
+            .unwrap_or_else(|_| {
+                if serializer.property_present(c_str!("hs-clk-div")) {
+                    dev_err!(dev, "invalid value for hs-clk-div, using default\n");
+                } else {
+                    dev_info!(dev, "hs-clk-div property not found, set to default value\n");
+                }
+                HsClkDiv::Div4
+            });
+        dev_info!(dev, "hs-clk-div: {hs_clk_div:?}\n");
+
+        let div_m_val = match get_u32(c_str!("div-m-val"), 1) {
+            v if clkout_div_fits(v, ti953::DIV_M_VAL, ti953::HS_CLK_DIV) => v,
+            v => {
+                dev_warn!(dev, "div-m-val ({v}) out of range, using default\n");
+                1
             }
         };
-        dev_info!(
-            dev,
-            "hs-clk-div: {hs_clk_div} (div by {})\n",
-            1 << hs_clk_div
-        );
-
-        let div_m_val = get_u32(c_str!("div-m-val"), 1);
-        let div_n_val = get_u32(c_str!("div-n-val"), 0x28);
-
-        let i2c_address = get_u32(c_str!("i2c-address"), 0x18);
-
-        let Some(i2c_client) = i2c_client.new_client_device(i2c_address as u16) else {
-            dev_info!(dev, "failed to add i2c client for ds90ub953\n");
-            continue;
+        let div_n_val = match get_u32(c_str!("div-n-val"), 0x28) {
+            v if clkout_div_fits(v, ti953::DIV_N_VAL, 8) => v,
+            v => {
+                dev_warn!(dev, "div-n-val ({v}) out of range, using default\n");
+                0x28
+            }
         };
 
-        let regmap = regmap::Regmap::init_i2c(&i2c_client, &REGMAP_CONFIG).map_err(|err| {
+        let i2c_address = get_u32(
+            c_str!("i2c-address"),
+            default_ser_alias(i2c_client.addr().into(), i),
+        );
+        if !i2c_addr_is_valid(i2c_address) {
             dev_err!(
                 dev,
-                "regmap init of subdevice failed ({})\n",
-                err.to_errno()
+                "invalid i2c-address (0x{i2c_address:x}) for ds90ub953, skipping\n"
             );
-            err
-        })?;
+            continue;
+        }
 
         // get i2c-slave addresse
         let i2c_slave = match serializer.property_get_reference_args(
@@ -1844,26 +3303,101 @@ struct Ds90ub953GpioConfig {
             Ok((_, i2c_addresses)) => i2c_addresses,
         };
 
-        // get slave-aliases
-        let i2c_alias = match serializer.property_get_reference_args(
-            c_str!("slave-alias"),
-            fwnode::NArgs::Prop(c_str!("list-cells")),
-            0,
-        ) {
-            Err(_) => {
-                dev_info!(dev, "reading i2c slave-alias addresses failed\n");
-                ArrayVec::default()
+        // get slave-aliases
+        let i2c_alias = match serializer.property_get_reference_args(
+            c_str!("slave-alias"),
+            fwnode::NArgs::Prop(c_str!("list-cells")),
+            0,
+        ) {
+            Err(_) => {
+                dev_info!(dev, "reading i2c slave-alias addresses failed\n");
+                ArrayVec::default()
+            }
+            Ok((_, i2c_addresses)) => i2c_addresses,
+        };
+
+        if i2c_slave.len() != i2c_alias.len() {
+            dev_warn!(
+                dev,
+                "{} i2c-slave but {} slave-alias addresses, ignoring the unpaired ones\n",
+                i2c_slave.len(),
+                i2c_alias.len()
+            );
+        }
+        let mut i2c_aliases = ArrayVec::default();
+        for (&slave, &alias) in i2c_slave.as_ref().iter().zip(i2c_alias.as_ref()) {
+            let (Ok(slave), Ok(alias)) = (u32::try_from(slave), u32::try_from(alias)) else {
+                dev_warn!(dev, "invalid slave alias pair, skipping\n");
+                continue;
+            };
+            if !i2c_addr_is_valid(slave) || !i2c_addr_is_valid(alias) {
+                dev_warn!(
+                    dev,
+                    "invalid slave addr 0x{slave:X} or alias addr 0x{alias:X}, skipping\n"
+                );
+                continue;
+            }
+            if i2c_aliases.try_extend([(slave, alias)]).is_err() {
+                dev_warn!(
+                    dev,
+                    "more than {NUM_ALIAS} slave alias pairs, ignoring the rest\n"
+                );
+                break;
+            }
+            dev_info!(dev, "slave addr: 0x{slave:X}, alias addr: 0x{alias:X}\n");
+        }
+        dev_info!(dev, "num of slave alias pairs: {}\n", i2c_aliases.len());
+
+        // the deserializer answers on the serializer address and the slave aliases, so they must
+        // not collide before a dummy client takes the serializer address
+        let mut addrs = ArrayVec::<{ NUM_ALIAS + 1 }, u32>::default();
+        let aliases = i2c_aliases.as_ref().iter().map(|&(_, alias)| alias);
+        if let Err(addr) = addrs.try_extend(core::iter::once(i2c_address).chain(aliases)) {
+            dev_err!(
+                dev,
+                "too many i2c addresses for ds90ub953 (0x{addr:02x}), skipping\n"
+            );
+            continue;
+        }
+        let in_use = |addr: &u32| {
+            res.iter().flatten().any(|ser: &Ds90ub953| {
+                ser.i2c_address == *addr
+                    || ser
+                        .i2c_aliases
+                        .as_ref()
+                        .iter()
+                        .any(|&(_, alias)| alias == *addr)
+            })
+        };
+        let conflict = first_address_conflict(i2c_client.addr().into(), addrs.as_ref())
+            .or_else(|| addrs.as_ref().iter().copied().find(in_use));
+        if let Some(addr) = conflict {
+            dev_err!(
+                dev,
+                "i2c address 0x{addr:02x} is used more than once, serializer and slave aliases must be distinct and differ from the deserializer address, skipping\n"
+            );
+            continue;
+        }
+
+        let i2c_client = match i2c_client.new_dummy(i2c_address as u16) {
+            Ok(i2c_client) => i2c_client,
+            Err(e) if e == EBUSY => {
+                dev_err!(
+                    dev,
+                    "i2c address 0x{i2c_address:02x} of ds90ub953 already in use, skipping\n"
+                );
+                continue;
             }
-            Ok((_, i2c_addresses)) => {
-                // TODO what if the two lengths are not equal?
-                dev_info!(dev, "num of slave alias pairs: {}\n", i2c_addresses.len());
-                i2c_addresses
+            Err(e) => {
+                dev_info!(dev, "failed to add i2c client for ds90ub953 ({e})\n");
+                continue;
             }
         };
 
-        for (slave, alias) in i2c_slave.as_ref().iter().zip(i2c_alias.as_ref()) {
-            dev_info!(dev, "slave addr: 0x{slave:X}, alias addr: 0x{alias:X}\n",);
-        }
+        let regmap = regmap::Regmap::init_i2c(&i2c_client, &SER_REGMAP_CONFIG).map_err(|err| {
+            dev_err!(dev, "regmap init of subdevice failed ({err})\n");
+            err
+        })?;
 
         let continuous_clock = serializer.property_read_bool(c_str!("continuous-clock"));
         if continuous_clock {
@@ -1872,14 +3406,105 @@ struct Ds90ub953GpioConfig {
             dev_info!(dev, "discontinuous clock used\n");
         }
 
-        let i2c_pass_through_all = serializer.property_read_bool(c_str!("i2c-pass-through-all"));
-        if i2c_pass_through_all {
-            dev_info!(dev, "i2c-pass-through-all enabled\n");
+        // one clock lane followed by up to four data lanes
+        let csi_lane_polarity = if serializer.property_present(c_str!("csi-lane-polarities")) {
+            match serializer
+                .property_count_elem::<u32>(c_str!("csi-lane-polarities"))
+                .and_then(|len| {
+                    if len > 5 {
+                        return Err(EINVAL);
+                    }
+                    serializer.property_read_array_vec::<u32>(c_str!("csi-lane-polarities"), len)
+                }) {
+                Ok(polarities) => CsiLanePolarity::from_dt(&polarities),
+                Err(_) => {
+                    dev_err!(dev, "invalid csi-lane-polarities, using default\n");
+                    CsiLanePolarity::default()
+                }
+            }
         } else {
-            dev_info!(dev, "i2c-pass-through-all disabled\n");
-        }
+            CsiLanePolarity::default()
+        };
+        dev_info!(dev, "csi lane polarity: {csi_lane_polarity:?}\n");
+
+        let i2c_forwarding = I2cFwd {
+            pass_through: !serializer.property_read_bool(c_str!("i2c-pass-through-disable")),
+            pass_through_all: serializer.property_read_bool(c_str!("i2c-pass-through-all")),
+            auto_ack: serializer.property_read_bool(c_str!("i2c-auto-ack")),
+        };
+        dev_info!(dev, "i2c forwarding: {i2c_forwarding:?}\n");
+
+        let csi_forward_options = CsiFwdOpts {
+            length_errors: !serializer.property_read_bool(c_str!("csi-drop-length-errors")),
+            ecc_errors: !serializer.property_read_bool(c_str!("csi-drop-ecc-errors")),
+            checksum_errors: !serializer.property_read_bool(c_str!("csi-drop-checksum-errors")),
+            wait_frame_start: !serializer
+                .property_read_bool(c_str!("csi-wait-frame-start-disable")),
+        };
+        dev_info!(dev, "csi forward options: {csi_forward_options:?}\n");
+
+        let input_mode = if serializer
+            .property_match_string(c_str!("input-mode"), c_str!("dvp"))
+            .is_ok()
+        {
+            InputMode::Dvp(DvpConfig {
+                line_valid_invert: serializer.property_read_bool(c_str!("dvp-line-valid-invert")),
+                frame_valid_invert: serializer.property_read_bool(c_str!("dvp-frame-valid-invert")),
+            })
+        } else {
+            InputMode::Csi
+        };
+        dev_info!(dev, "input-mode: {input_mode:?}\n");
+
+        let virtual_channel_map = match get_u32(c_str!("virtual-channel-map"), 0xE4) {
+            v @ 0..=0xff if vc_map_is_valid(v as u8) => v as u8,
+            v => {
+                dev_err!(
+                    dev,
+                    "invalid value (0x{v:X}) for virtual-channel-map, using default\n"
+                );
+                0xE4
+            }
+        };
+
+        let pass_threshold = match get_u32(c_str!("pass-threshold"), 1) {
+            v @ 1..=3 => v,
+            v => {
+                dev_err!(
+                    dev,
+                    "invalid value ({v}) for pass-threshold, using default\n"
+                );
+                1
+            }
+        };
+        let pass_control = PassControl {
+            threshold: pass_threshold,
+            watchdog_disable: serializer.property_read_bool(c_str!("pass-watchdog-disable")),
+            parity_error: serializer.property_read_bool(c_str!("pass-parity-error")),
+        };
+
+        let relock_time = serializer.property_read::<u32>(c_str!("aeq-relock-time"), None);
+        let aeq_relock_time = match relock_time {
+            Err(_) => None,
+            Ok(v @ 0..=7) => Some(v as u8),
+            Ok(v) => {
+                dev_err!(dev, "invalid value ({v}) for aeq-relock-time, ignoring\n");
+                None
+            }
+        };
 
-        let virtual_channel_map = get_u32(c_str!("virtual-channel-map"), 0xE4);
+        let embedded_dtype = serializer.property_read::<u32>(c_str!("embedded-data-type"), None);
+        let embedded_dtype = match embedded_dtype {
+            Err(_) => None,
+            Ok(v @ 0..=0x3f) => Some(v as u8),
+            Ok(v) => {
+                dev_err!(
+                    dev,
+                    "invalid value (0x{v:X}) for embedded-data-type, ignoring\n"
+                );
+                None
+            }
+        };
 
         res[i] = Some(Ds90ub953 {
             i2c_client,
@@ -1888,15 +3513,20 @@ struct Ds90ub953GpioConfig {
             rx_channel,
             test_pattern,
             csi_lane_count,
-            i2c_slave,
-            i2c_alias,
+            i2c_aliases,
             hs_clk_div,
             i2c_address,
             continuous_clock,
-            i2c_pass_through_all,
+            csi_lane_polarity,
+            i2c_forwarding,
+            csi_forward_options,
+            input_mode,
             div_m_val,
             div_n_val,
             virtual_channel_map,
+            pass_control,
+            aeq_relock_time,
+            embedded_dtype,
         });
     }
 
@@ -1914,13 +3544,22 @@ fn init(&mut self) -> Result<()> {
         let dev_id = self.read(ti953::REG_I2C_DEV_ID)?;
 
         let mut id_code = [0; ti953::RX_ID_LENGTH];
-        for (i, byte) in id_code.iter_mut().enumerate() {
-            *byte = self.read(ti953::REG_FPD3_RX_ID0 + i as u32)? as u8;
-        }
+        self.regmap
+            .bulk_read(ti953::REG_FPD3_RX_ID0, &mut id_code)
+            .map_err(|err| {
+                dev_err!(dev, "cannot read ID code ({err})!\n");
+                err
+            })?;
         let id_code = BStr::from_bytes(&id_code);
 
         dev_info!(dev, "device ID: 0x{dev_id:x}, code: {id_code}\n");
 
+        // override the strapped input mode
+        self.write(ti953::REG_MODE_SEL, self.input_mode.to_mode_sel())?;
+        if let InputMode::Dvp(dvp) = self.input_mode {
+            self.write(ti953::REG_DVP_CFG, dvp.to_dvp_cfg())?;
+        }
+
         // set to csi lanes
         let value = match self.csi_lane_count {
             1 => ti953::CSI_LANE_SEL1,
@@ -1935,13 +3574,24 @@ fn init(&mut self) -> Result<()> {
                 | (if self.continuous_clock { 1 } else { 0 } << ti953::CONTS_CLK),
         )?;
 
+        // invert swapped csi lanes
+        self.write(
+            ti953::REG_CSI_POL_SEL,
+            self.csi_lane_polarity.to_csi_pol_sel(),
+        )?;
+        self.write(
+            ti953::REG_CSI_LP_POLARITY,
+            self.csi_lane_polarity.to_csi_lp_polarity(),
+        )?;
+
         // set GPIO0 as output
         self.write(ti953::REG_GPIO_CTRL, 0x1E)?;
 
         // set clock output frequency
         self.write(
             ti953::REG_CLKOUT_CTRL0,
-            (self.hs_clk_div << ti953::HS_CLK_DIV) | (self.div_m_val << ti953::DIV_M_VAL),
+            (self.hs_clk_div.to_hs_clk_div() << ti953::HS_CLK_DIV)
+                | (self.div_m_val << ti953::DIV_M_VAL),
         )?;
 
         self.write(ti953::REG_CLKOUT_CTRL1, self.div_n_val << ti953::DIV_N_VAL)?;
@@ -1989,12 +3639,22 @@ fn init_testpattern(&mut self) -> Result<()> {
         let i2c_client = self.i2c_client.clone();
         let dev = i2c_client.as_ref();
 
-        for (reg, val) in DS90UB95X_TP_REG_VAL {
-            self.write(reg, val).map_err(|err| {
-                dev_info!(dev, "953: enable test pattern failed\n");
-                err
-            })?;
-        }
+        let mut init = || -> Result<()> {
+            self.write(
+                ti953::REG_IND_ACC_CTL,
+                ind_acc_ctl(ti954::REG_IA_PATTERN_GEN_PAGE_BLOCK_SELECT, false),
+            )?;
+            let seq = indirect_write_sequence(
+                ti953::REG_IND_ACC_ADDR,
+                ti953::REG_IND_ACC_DATA,
+                &DS90UB95X_TP_REG_VAL,
+            );
+            self.regmap.multi_reg_write(seq.as_flattened())
+        };
+        init().map_err(|err| {
+            dev_info!(dev, "953: enable test pattern failed\n");
+            err
+        })?;
         dev_info!(dev, "953: enable test pattern successful\n");
         Ok(())
     }
@@ -2003,7 +3663,7 @@ fn read(&mut self, register: u32) -> Result<u32> {
         self.regmap.read(register).map_err(|err| {
             dev_err!(
                 self.i2c_client.as_ref(),
-                "cannot read register 0x{register:02x} ({err:?})!\n"
+                "cannot read register 0x{register:02x} ({err})!\n"
             );
             err
         })
@@ -2013,16 +3673,18 @@ fn write(&mut self, register: u32, value: u32) -> Result<()> {
         self.regmap.write(register, value).map_err(|err| {
             dev_err!(
                 self.i2c_client.as_ref(),
-                "cannot write register 0x{register:02x} ({err:?})!\n"
+                "cannot write register 0x{register:02x} ({err})!\n"
             );
             err
         })
     }
 }
 
-impl Drop for Ds90ub954 {
+// The state is only powered down once the last reference to it is gone.
+impl Drop for Ds90ub954Data {
     fn drop(&mut self) {
         pr_info!("dropping ds90ub954\n");
+        self.shutdown();
         self.pwr_disable();
     }
 }
@@ -2057,3 +3719,673 @@ fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "{}", self.to_u32())
     }
 }
+
+/// CSI PHY calibration mode of the deserializer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CalMode {
+    /// Trigger a single calibration. The bit is self-clearing.
+    Single,
+    /// Periodically recalibrate the CSI PHY.
+    Periodic,
+}
+
+impl CalMode {
+    /// Bits to set in `REG_CSI_CTL2` for this mode.
+    const fn to_csi_ctl2(self) -> u32 {
+        match self {
+            CalMode::Single => 1 << ti954::CSI_CAL_SINGLE,
+            CalMode::Periodic => 1 << ti954::CSI_CAL_PERIODIC,
+        }
+    }
+}
+
+/// Update a `REG_CSI_CTL2` value for the calibration `mode`, keeping the other settings.
+const fn csi_ctl2_cal(value: u32, mode: CalMode) -> u32 {
+    let value = value & !((1 << ti954::CSI_CAL_SINGLE) | (1 << ti954::CSI_CAL_PERIODIC));
+    value | mode.to_csi_ctl2()
+}
+
+kernel::static_assert!(csi_ctl2_cal(0x00, CalMode::Single) == 0x02);
+kernel::static_assert!(csi_ctl2_cal(0x00, CalMode::Periodic) == 0x01);
+kernel::static_assert!(csi_ctl2_cal(0x01, CalMode::Single) == 0x02);
+kernel::static_assert!(csi_ctl2_cal(0x06, CalMode::Periodic) == 0x05);
+kernel::static_assert!(csi_ctl2_cal(0xff, CalMode::Single) == 0xfe);
+
+/// Update a `REG_CSI_CTL` value for entering or leaving the ultra-low-power state.
+const fn csi_ctl_ulp(value: u32, enable: bool) -> u32 {
+    if enable {
+        value | (1 << ti954::CSI_ULP)
+    } else {
+        value & !(1 << ti954::CSI_ULP)
+    }
+}
+
+kernel::static_assert!(csi_ctl_ulp(0x00, true) == 0x04);
+kernel::static_assert!(csi_ctl_ulp(0xfb, true) == 0xff);
+kernel::static_assert!(csi_ctl_ulp(0xff, false) == 0xfb);
+kernel::static_assert!(csi_ctl_ulp(0x43, false) == 0x43);
+
+/// Use of the four CSI-2 data lanes of the deserializer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LanePartition {
+    /// All four lanes belong to CSI port 0.
+    Single,
+    /// Two lanes for each of the two CSI ports.
+    Split,
+}
+
+impl LanePartition {
+    /// Number of data lanes of each CSI port.
+    const fn lane_count(self) -> u32 {
+        match self {
+            LanePartition::Single => 4,
+            LanePartition::Split => 2,
+        }
+    }
+
+    /// Replace the `CSI_LANE_COUNT` field of a `REG_CSI_CTL` value.
+    const fn apply_to_csi_ctl(self, value: u32) -> u32 {
+        let lanes = match self {
+            LanePartition::Single => ti954::CSI_4_LANE,
+            LanePartition::Split => ti954::CSI_2_LANE,
+        };
+        (value & !(0b11 << ti954::CSI_LANE_COUNT)) | (lanes << ti954::CSI_LANE_COUNT)
+    }
+}
+
+kernel::static_assert!(LanePartition::Single.apply_to_csi_ctl(0x23) == 0x03);
+kernel::static_assert!(LanePartition::Split.apply_to_csi_ctl(0x03) == 0x23);
+kernel::static_assert!(LanePartition::Split.apply_to_csi_ctl(0x33) == 0x23);
+kernel::static_assert!(LanePartition::Single.lane_count() == 4);
+kernel::static_assert!(LanePartition::Split.lane_count() == 2);
+
+/// Behavior of the deserializer's CSI output pins.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OutputMode {
+    /// Outputs are only enabled while a receiver port has lock and pass, and are held in the
+    /// LP-11 state otherwise.
+    Auto,
+    /// Outputs are always enabled, regardless of the receiver port state.
+    AlwaysOn,
+}
+
+impl OutputMode {
+    /// Bits to set in `REG_GENERAL_CFG` for this mode.
+    fn to_general_cfg(self) -> u32 {
+        match self {
+            OutputMode::Auto => {
+                (1 << ti954::OUTPUT_ENABLE) | (1 << ti954::OUTPUT_SLEEP_STATE_SELECT)
+            }
+            OutputMode::AlwaysOn => (1 << ti954::OUTPUT_ENABLE) | (1 << ti954::OUTPUT_EN_MODE),
+        }
+    }
+}
+
+/// Configuration of the PASS signal of an RX port.
+#[derive(Debug, Clone, Copy)]
+struct PassControl {
+    /// Number of consecutive valid frames required before PASS asserts (1 to 3).
+    threshold: u32,
+    /// Disable the watchdog deasserting PASS when no frames are received.
+    watchdog_disable: bool,
+    /// Deassert PASS on parity errors.
+    parity_error: bool,
+}
+
+impl PassControl {
+    /// Value of `REG_PORT_PASS_CTL` for this configuration.
+    fn to_port_pass_ctl(self) -> u32 {
+        ((self.threshold.clamp(1, 3) - 1) << ti954::PASS_THRESHOLD)
+            | (if self.watchdog_disable { 1 } else { 0 } << ti954::PASS_WDOG_DIS)
+            | (if self.parity_error { 1 } else { 0 } << ti954::PASS_PARITY_ERR)
+    }
+}
+
+/// Serial rate of the CSI-2 lanes, `csi-lane-speed` in the device tree is given in Mbps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CsiLaneSpeed {
+    Mbps400,
+    Mbps800,
+    Mbps1600,
+}
+
+impl CsiLaneSpeed {
+    /// Value of the `CSI_TX_SPEED` field of `REG_CSI_PLL_CTL`, assuming a 25 MHz REFCLK.
+    const fn to_csi_tx_speed(self) -> u32 {
+        match self {
+            CsiLaneSpeed::Mbps1600 => 0b00,
+            CsiLaneSpeed::Mbps800 => 0b10,
+            CsiLaneSpeed::Mbps400 => 0b11,
+        }
+    }
+}
+
+impl fwnode::FromProperty for CsiLaneSpeed {
+    fn from_property(value: u32) -> Option<Self> {
+        match value {
+            400 => Some(CsiLaneSpeed::Mbps400),
+            800 => Some(CsiLaneSpeed::Mbps800),
+            1600 => Some(CsiLaneSpeed::Mbps1600),
+            _ => None,
+        }
+    }
+}
+
+kernel::static_assert!(CsiLaneSpeed::Mbps1600.to_csi_tx_speed() == 0b00);
+kernel::static_assert!(CsiLaneSpeed::Mbps800.to_csi_tx_speed() == 0b10);
+kernel::static_assert!(CsiLaneSpeed::Mbps400.to_csi_tx_speed() == 0b11);
+
+/// Divider of the serializer's high-speed clock for the reference clock output,
+/// `hs-clk-div` in the device tree is the divisor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum HsClkDiv {
+    Div1,
+    Div2,
+    Div4,
+    Div8,
+    Div16,
+}
+
+impl HsClkDiv {
+    /// Value of the `HS_CLK_DIV` field of `REG_CLKOUT_CTRL0`.
+    const fn to_hs_clk_div(self) -> u32 {
+        match self {
+            HsClkDiv::Div1 => 0b000,
+            HsClkDiv::Div2 => 0b001,
+            HsClkDiv::Div4 => 0b010,
+            HsClkDiv::Div8 => 0b011,
+            HsClkDiv::Div16 => 0b100,
+        }
+    }
+}
+
+impl fwnode::FromProperty for HsClkDiv {
+    fn from_property(value: u32) -> Option<Self> {
+        match value {
+            1 => Some(HsClkDiv::Div1),
+            2 => Some(HsClkDiv::Div2),
+            4 => Some(HsClkDiv::Div4),
+            8 => Some(HsClkDiv::Div8),
+            16 => Some(HsClkDiv::Div16),
+            _ => None,
+        }
+    }
+}
+
+kernel::static_assert!(HsClkDiv::Div1.to_hs_clk_div() == 0b000);
+kernel::static_assert!(HsClkDiv::Div4.to_hs_clk_div() == 0b010);
+kernel::static_assert!(HsClkDiv::Div16.to_hs_clk_div() == 0b100);
+
+/// Forwarding of I2C transactions to the remote side of an RX port.
+#[derive(Debug, Clone, Copy)]
+struct I2cFwd {
+    /// Forward transactions to the serializer alias and the configured slave aliases.
+    pass_through: bool,
+    /// Forward all transactions, regardless of their address.
+    pass_through_all: bool,
+    /// Acknowledge forwarded writes locally instead of waiting for the remote side.
+    auto_ack: bool,
+}
+
+impl I2cFwd {
+    /// Bits to set in `REG_BCC_CONFIG` for this configuration.
+    fn to_bcc_config(self) -> u32 {
+        (if self.pass_through { 1 } else { 0 } << ti954::I2C_PASS_THROUGH)
+            | (if self.pass_through_all { 1 } else { 0 } << ti954::I2C_PASS_THROUGH_ALL)
+            | (if self.auto_ack { 1 } else { 0 } << ti954::AUTO_ACK_ALL)
+    }
+}
+
+/// Forwarding of the CSI-2 packets received on an RX port, see `REG_PORT_CONFIG`.
+///
+/// The hardware defaults forward everything, but only from the first frame start on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct CsiFwdOpts {
+    /// Forward packets with a length error instead of dropping them.
+    length_errors: bool,
+    /// Forward packets with an uncorrectable header ECC error, needed for sensors that don't
+    /// send an ECC.
+    ecc_errors: bool,
+    /// Forward packets with a payload checksum error.
+    checksum_errors: bool,
+    /// Hold back packets until the first frame start, so the output never begins mid-frame.
+    wait_frame_start: bool,
+}
+
+impl CsiFwdOpts {
+    /// Replace the forwarding bits in the `REG_PORT_CONFIG` value `value`.
+    const fn apply_to_port_config(self, value: u32) -> u32 {
+        let mask = (1 << ti954::CSI_FWD_LEN)
+            | (1 << ti954::CSI_FWD_ECC)
+            | (1 << ti954::CSI_FWD_CKSUM)
+            | (1 << ti954::CSI_WAIT_FS);
+        (value & !mask)
+            | ((self.length_errors as u32) << ti954::CSI_FWD_LEN)
+            | ((self.ecc_errors as u32) << ti954::CSI_FWD_ECC)
+            | ((self.checksum_errors as u32) << ti954::CSI_FWD_CKSUM)
+            | ((self.wait_frame_start as u32) << ti954::CSI_WAIT_FS)
+    }
+}
+
+kernel::static_assert!(
+    CsiFwdOpts {
+        length_errors: true,
+        ecc_errors: true,
+        checksum_errors: true,
+        wait_frame_start: true,
+    }
+    .apply_to_port_config(0x00)
+        == 0x78
+);
+kernel::static_assert!(
+    CsiFwdOpts {
+        length_errors: false,
+        ecc_errors: false,
+        checksum_errors: false,
+        wait_frame_start: false,
+    }
+    .apply_to_port_config(0xff)
+        == 0x87
+);
+kernel::static_assert!(
+    CsiFwdOpts {
+        length_errors: true,
+        ecc_errors: false,
+        checksum_errors: true,
+        wait_frame_start: false,
+    }
+    .apply_to_port_config(0x04)
+        == 0x2c
+);
+kernel::static_assert!(
+    CsiFwdOpts {
+        length_errors: false,
+        ecc_errors: false,
+        checksum_errors: false,
+        wait_frame_start: true,
+    }
+    .apply_to_port_config(0x78)
+        == 0x40
+);
+
+/// Input of a serializer.
+#[derive(Debug, Clone, Copy)]
+enum InputMode {
+    /// CSI-2 sensor, synchronous to the deserializer's back channel clock.
+    Csi,
+    /// Parallel (DVP) sensor.
+    Dvp(DvpConfig),
+}
+
+impl InputMode {
+    /// Value of `REG_MODE_SEL` overriding the strapped mode with this one.
+    const fn to_mode_sel(self) -> u32 {
+        let mode = match self {
+            InputMode::Csi => ti953::MODE_SYNC,
+            InputMode::Dvp(_) => ti953::MODE_DVP,
+        };
+        (mode << ti953::MODE) | (1 << ti953::MODE_OV)
+    }
+}
+
+/// Framing of a parallel (DVP) serializer input.
+#[derive(Debug, Clone, Copy)]
+struct DvpConfig {
+    /// The sensor's line valid signal is active low.
+    line_valid_invert: bool,
+    /// The sensor's frame valid signal is active low.
+    frame_valid_invert: bool,
+}
+
+impl DvpConfig {
+    /// Value of `REG_DVP_CFG` for this configuration.
+    const fn to_dvp_cfg(self) -> u32 {
+        (if self.line_valid_invert { 1 } else { 0 } << ti953::DVP_LV_INV)
+            | (if self.frame_valid_invert { 1 } else { 0 } << ti953::DVP_FV_IN)
+    }
+}
+
+kernel::static_assert!(InputMode::Csi.to_mode_sel() == 0x10);
+kernel::static_assert!(
+    InputMode::Dvp(DvpConfig {
+        line_valid_invert: false,
+        frame_valid_invert: false,
+    })
+    .to_mode_sel()
+        == 0x15
+);
+kernel::static_assert!(
+    DvpConfig {
+        line_valid_invert: true,
+        frame_valid_invert: false,
+    }
+    .to_dvp_cfg()
+        == 0x01
+);
+kernel::static_assert!(
+    DvpConfig {
+        line_valid_invert: false,
+        frame_valid_invert: true,
+    }
+    .to_dvp_cfg()
+        == 0x02
+);
+
+/// Debounce time of the `pass` and `lock` inputs.
+const STATUS_GPIO_DEBOUNCE_US: u32 = 5000;
+
+/// Time the back channel of a serializer gets to come up after forwarding is enabled.
+const BACKCHANNEL_TIMEOUT_MS: u32 = 500;
+
+/// REFCLK frequency assumed when the device tree doesn't specify one.
+const DEFAULT_REFCLK_HZ: u32 = 25_000_000;
+
+/// Convert a REFCLK frequency in Hz to whole MHz, rounding to the nearest value.
+///
+/// Returns `None` if the result doesn't fit the 8-bit `REG_REFCLK_FREQ` field.
+const fn refclk_hz_to_mhz(hz: u32) -> Option<u8> {
+    let mhz = (hz as u64 + 500_000) / 1_000_000;
+    if mhz == 0 || mhz > u8::MAX as u64 {
+        None
+    } else {
+        Some(mhz as u8)
+    }
+}
+
+/// Encode a REFCLK frequency in MHz for `REG_REFCLK_FREQ`.
+const fn refclk_freq_reg(mhz: u8) -> u32 {
+    (mhz as u32) << ti954::REFCLK_FREQ
+}
+
+kernel::static_assert!(refclk_freq_reg(25) == 0x19);
+kernel::static_assert!(refclk_freq_reg(u8::MAX) == 0xff);
+kernel::static_assert!(matches!(refclk_hz_to_mhz(DEFAULT_REFCLK_HZ), Some(25)));
+kernel::static_assert!(matches!(refclk_hz_to_mhz(26_999_999), Some(27)));
+kernel::static_assert!(refclk_hz_to_mhz(0).is_none());
+kernel::static_assert!(refclk_hz_to_mhz(300_000_000).is_none());
+
+/// Polarity inversion of the CSI lanes of the serializer.
+#[derive(Debug, Default, Clone, Copy)]
+struct CsiLanePolarity {
+    clock: bool,
+    data: [bool; 4],
+}
+
+impl CsiLanePolarity {
+    /// Parse the `csi-lane-polarities` property.
+    ///
+    /// Like the `lane-polarities` property of video interfaces, the first entry is the clock
+    /// lane, followed by the data lanes. Missing entries mean normal polarity.
+    fn from_dt(polarities: &[u32]) -> Self {
+        let mut res = Self::default();
+        let mut polarities = polarities.iter().map(|&p| p != 0);
+        res.clock = polarities.next().unwrap_or(false);
+        for (data, inverted) in res.data.iter_mut().zip(polarities) {
+            *data = inverted;
+        }
+        res
+    }
+
+    /// Encode as `REG_CSI_POL_SEL` value, for the high-speed signals.
+    const fn to_csi_pol_sel(self) -> u32 {
+        (if self.data[0] { 1 } else { 0 } << ti953::POLARITY_D0)
+            | (if self.data[1] { 1 } else { 0 } << ti953::POLARITY_D1)
+            | (if self.data[2] { 1 } else { 0 } << ti953::POLARITY_D2)
+            | (if self.data[3] { 1 } else { 0 } << ti953::POLARITY_D3)
+            | (if self.clock { 1 } else { 0 } << ti953::POLARITY_CK0)
+    }
+
+    /// Encode as `REG_CSI_LP_POLARITY` value, for the low-power signals.
+    const fn to_csi_lp_polarity(self) -> u32 {
+        (if self.data[0] { 1 } else { 0 } << ti953::POL_LP_DATA)
+            | (if self.data[1] { 1 } else { 0 } << (ti953::POL_LP_DATA + 1))
+            | (if self.data[2] { 1 } else { 0 } << (ti953::POL_LP_DATA + 2))
+            | (if self.data[3] { 1 } else { 0 } << (ti953::POL_LP_DATA + 3))
+            | (if self.clock { 1 } else { 0 } << ti953::POL_LP_CLK0)
+    }
+}
+
+kernel::static_assert!(
+    CsiLanePolarity {
+        clock: false,
+        data: [false; 4]
+    }
+    .to_csi_pol_sel()
+        == 0
+);
+kernel::static_assert!(
+    CsiLanePolarity {
+        clock: true,
+        data: [true, false, false, true]
+    }
+    .to_csi_pol_sel()
+        == 0b1_1001
+);
+kernel::static_assert!(
+    CsiLanePolarity {
+        clock: true,
+        data: [false, true, true, false]
+    }
+    .to_csi_lp_polarity()
+        == 0b1_0110
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Access {
+        Read(u32),
+        Write(u32, u32),
+    }
+
+    /// Register map that records the accesses, reads return the value last written.
+    struct MockRegs {
+        regs: [u32; 0x100],
+        accesses: KVec<Access>,
+        selected_ia_config: Option<u32>,
+        selected_rx_port: Option<RxPort>,
+    }
+
+    impl MockRegs {
+        fn new() -> Self {
+            Self {
+                regs: [0; 0x100],
+                accesses: KVec::new(),
+                selected_ia_config: None,
+                selected_rx_port: None,
+            }
+        }
+    }
+
+    impl RegisterAccess for MockRegs {
+        fn read(&mut self, register: u32) -> Result<u32> {
+            self.accesses.push(Access::Read(register), GFP_KERNEL)?;
+            Ok(self.regs[register as usize])
+        }
+
+        fn write(&mut self, register: u32, value: u32) -> Result<()> {
+            self.accesses
+                .push(Access::Write(register, value), GFP_KERNEL)?;
+            self.regs[register as usize] = value;
+            Ok(())
+        }
+
+        fn multi_reg_write(&mut self, regs: &[(u32, u32)]) -> Result<()> {
+            for &(register, value) in regs {
+                self.write(register, value)?;
+            }
+            Ok(())
+        }
+
+        fn selected_ia_config(&mut self) -> &mut Option<u32> {
+            &mut self.selected_ia_config
+        }
+
+        fn selected_rx_port(&mut self) -> &mut Option<RxPort> {
+            &mut self.selected_rx_port
+        }
+    }
+
+    #[test]
+    fn test_ia_select_is_cached() {
+        use Access::*;
+        let mut regs = MockRegs::new();
+
+        assert_eq!(regs.indirect_write(1, 0x10, 0xaa), Ok(()));
+        // same page and direction, the select is skipped
+        assert_eq!(regs.indirect_write(1, 0x11, 0xbb), Ok(()));
+        // reading needs a different `REG_IND_ACC_CTL` value
+        assert_eq!(regs.indirect_read(1, 0x10), Ok(0xbb));
+        assert_eq!(regs.indirect_read(1, 0x11), Ok(0xbb));
+
+        assert_eq!(
+            regs.accesses[..],
+            [
+                Write(ti954::REG_IND_ACC_CTL, ind_acc_ctl(1, false)),
+                Write(ti954::REG_IND_ACC_ADDR, 0x10),
+                Write(ti954::REG_IND_ACC_DATA, 0xaa),
+                Write(ti954::REG_IND_ACC_ADDR, 0x11),
+                Write(ti954::REG_IND_ACC_DATA, 0xbb),
+                Write(ti954::REG_IND_ACC_CTL, ind_acc_ctl(1, true)),
+                Write(ti954::REG_IND_ACC_ADDR, 0x10),
+                Read(ti954::REG_IND_ACC_DATA),
+                Write(ti954::REG_IND_ACC_ADDR, 0x11),
+                Read(ti954::REG_IND_ACC_DATA),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_indirect_read_block() {
+        use Access::*;
+        let mut regs = MockRegs::new();
+        regs.regs[ti954::REG_IND_ACC_DATA as usize] = 0x5a;
+
+        let mut buf = [0u8; 3];
+        assert_eq!(regs.indirect_read_block(1, 0xf0, &mut buf), Ok(()));
+        assert_eq!(buf, [0x5a; 3]);
+        assert_eq!(
+            regs.accesses[..],
+            [
+                Write(ti954::REG_IND_ACC_CTL, ind_acc_ctl_auto_inc(1)),
+                Write(ti954::REG_IND_ACC_ADDR, 0xf0),
+                Read(ti954::REG_IND_ACC_DATA),
+                Read(ti954::REG_IND_ACC_DATA),
+                Read(ti954::REG_IND_ACC_DATA),
+            ]
+        );
+
+        // blocks past the end of the page are rejected without any access
+        let mut regs = MockRegs::new();
+        let mut buf = [0u8; 0x11];
+        assert_eq!(regs.indirect_read_block(1, 0xf0, &mut buf), Err(EINVAL));
+        assert!(regs.accesses.is_empty());
+    }
+
+    #[test]
+    fn test_write_test_pattern() {
+        use Access::*;
+        let page = ti954::REG_IA_PATTERN_GEN_PAGE_BLOCK_SELECT;
+        let mut regs = MockRegs::new();
+
+        assert_eq!(regs.write_test_pattern(true), Ok(()));
+        assert_eq!(regs.accesses.len(), 1 + 2 * DS90UB95X_TP_REG_VAL.0.len());
+        assert_eq!(
+            regs.accesses[..3],
+            [
+                Write(ti954::REG_IND_ACC_CTL, ind_acc_ctl(page, false)),
+                Write(ti954::REG_IND_ACC_ADDR, ti954::REG_IA_PGEN_CTL),
+                Write(ti954::REG_IND_ACC_DATA, pgen_ctl(true)),
+            ]
+        );
+
+        // disabling only clears the enable bit, on the page that is still selected
+        let mut regs = MockRegs {
+            selected_ia_config: Some(ind_acc_ctl(page, false)),
+            ..MockRegs::new()
+        };
+        assert_eq!(regs.write_test_pattern(false), Ok(()));
+        assert_eq!(
+            regs.accesses[..],
+            [
+                Write(ti954::REG_IND_ACC_ADDR, ti954::REG_IA_PGEN_CTL),
+                Write(ti954::REG_IND_ACC_DATA, pgen_ctl(false)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_recover_link_restarts_aeq() {
+        use Access::*;
+        let mut regs = MockRegs::new();
+        regs.regs[ti954::REG_AEQ_CTL2 as usize] = 0x41;
+
+        assert_eq!(regs.recover_link(RxPort::One), Ok(true));
+        assert_eq!(
+            regs.accesses[..],
+            [
+                Write(ti954::REG_FPD3_PORT_SEL, 0x12),
+                Read(ti954::REG_RX_PORT_STS1),
+                Read(ti954::REG_AEQ_CTL2),
+                Write(ti954::REG_AEQ_CTL2, 0x49),
+                Write(ti954::REG_AEQ_CTL2, 0x41),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_recover_link_locked() {
+        use Access::*;
+        let mut regs = MockRegs::new();
+        regs.regs[ti954::REG_RX_PORT_STS1 as usize] = 1 << ti954::LOCK_STS;
+
+        assert_eq!(regs.recover_link(RxPort::Zero), Ok(false));
+        assert_eq!(
+            regs.accesses[..],
+            [
+                Write(ti954::REG_FPD3_PORT_SEL, 0x01),
+                Read(ti954::REG_RX_PORT_STS1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_rx_port_both() {
+        let mut regs = MockRegs::new();
+
+        assert_eq!(
+            regs.read_rx_port(RxPort::Both, ti954::REG_RX_PORT_STS1),
+            Err(EINVAL)
+        );
+        assert!(regs.accesses.is_empty());
+    }
+
+    #[test]
+    fn test_write_ia_ctl() {
+        use Access::*;
+        let mut regs = MockRegs::new();
+
+        assert_eq!(regs.write_ia_ctl(0x04), Ok(()));
+        assert_eq!(regs.write_ia_ctl(0x04), Ok(()));
+        assert_eq!(regs.write_ia_ctl(0x05), Ok(()));
+        assert_eq!(regs.selected_ia_config, Some(0x05));
+
+        assert_eq!(
+            regs.accesses[..],
+            [
+                Write(ti954::REG_IND_ACC_CTL, 0x04),
+                Write(ti954::REG_IND_ACC_CTL, 0x05),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_require_serializer() {
+        assert_eq!(require_serializer::<()>(&[]), Err(ENODEV));
+        assert_eq!(require_serializer::<()>(&[None, None]), Err(ENODEV));
+        assert_eq!(require_serializer(&[None, Some(())]), Ok(()));
+        assert_eq!(require_serializer(&[Some(()), None]), Ok(()));
+    }
+}
@@ -9,7 +9,7 @@
     prelude::*,
     regmap::{self, BitFieldReadOps, BitFieldWriteOps, RawFieldWriteOps},
     regulator::{
-        driver::{Config, Desc, Device, Driver, RegmapHelpers, Status, Type},
+        driver::{Config, Desc, Device, Driver, RegmapHelpers, Rounding, Status, Type},
         Mode,
     },
     sync::{new_mutex, Arc, Mutex},
@@ -100,7 +100,8 @@
         6250,
         128,
         0,
-    );
+    )
+    .with_voltage_rounding(Rounding::Up);
 
 struct Ncv6336RegulatorData {
     fields: regmap::Fields<{ FIELD_DESCS.len() }>,
@@ -119,8 +120,19 @@ fn probe(client: &mut i2c::Client, _id_info: Option<&Self::IdInfo>) -> Result<Pi
             .with_access_ops::<AccessOps>()
             .with_max_register(0x16)
             .with_cache_type(regmap::CacheType::RbTree);
-        let regmap = Arc::new(regmap::Regmap::init_i2c(client, &config)?, GFP_KERNEL)?;
-        let fields = regmap::Fields::new(&regmap, &FIELD_DESCS)?;
+        let regmap = regmap::Regmap::init_i2c_arc(client, &config)?;
+        let mut fields = regmap::Fields::new(&regmap, &FIELD_DESCS)?;
+
+        // `regulator-initial-mode` is a mode number in the generic binding, this one is a name.
+        let dev = client.as_ref();
+        let initial_mode = c_str!("onnn,initial-mode");
+        if dev.as_fwnode().property_present(initial_mode) {
+            match dev.property_read_regulator_mode(initial_mode)? {
+                Mode::Normal => command::pwmvsel0::clear(&mut fields)?,
+                Mode::Fast => command::pwmvsel0::set(&mut fields)?,
+                mode => dev_warn!(dev, "unsupported initial mode {mode:?}, ignoring\n"),
+            }
+        }
 
         let data = Arc::pin_init(new_mutex!(Ncv6336RegulatorData { fields }), GFP_KERNEL)?;
         let config = Config::new(client.as_ref(), data.clone()).with_regmap(regmap.clone());
@@ -140,6 +152,10 @@ fn list_voltage(reg: &mut Device<Self::Data>, selector: u32) -> Result<i32> {
         reg.list_voltage_linear(selector)
     }
 
+    fn map_voltage(reg: &mut Device<Self::Data>, min_uv: i32, max_uv: i32) -> Result<i32> {
+        reg.map_voltage_linear(min_uv, max_uv)
+    }
+
     fn enable(reg: &mut Device<Self::Data>) -> Result {
         reg.enable_regmap()
     }
@@ -203,13 +219,11 @@ fn get_status(reg: &mut Device<Self::Data>) -> Result<Status> {
     }
 
     fn set_suspend_voltage(reg: &mut Device<Self::Data>, uv: i32) -> Result {
+        let selector = reg.map_voltage_linear(uv, i32::MAX)?;
+
         let data = reg.data();
         let fields = &mut data.lock().fields;
 
-        let quot = (uv - 600000) / 6250;
-        let rem = (uv - 600000) % 6250;
-        let selector = if rem > 0 { quot + 1 } else { quot };
-
         progvsel1::voutvsel1::write(fields, selector as _)
     }
 
@@ -38,6 +38,303 @@ pub fn push(&mut self, elem: T) {
     pub fn len(&self) -> usize {
         self.len
     }
+
+    /// Returns a raw pointer to the backing storage.
+    ///
+    /// Only the first [`ArrayVec::len`] elements are initialized.
+    pub fn as_ptr(&self) -> *const T {
+        self.array.as_ptr().cast()
+    }
+
+    /// Returns a raw mutable pointer to the backing storage, e.g. to pass it as an FFI
+    /// out-parameter.
+    ///
+    /// Only the first [`ArrayVec::len`] elements are initialized. Elements written past them only
+    /// become part of the vector after a call to [`ArrayVec::set_len`].
+    pub fn as_mut_ptr(&mut self) -> *mut T {
+        self.array.as_mut_ptr().cast()
+    }
+
+    /// Returns the remaining spare capacity of the vector.
+    ///
+    /// Elements written to it only become part of the vector after a call to
+    /// [`ArrayVec::set_len`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kernel::arrayvec::ArrayVec;
+    ///
+    /// let mut v = ArrayVec::<4, u32>::default();
+    /// v.push(1);
+    ///
+    /// let spare = v.spare_capacity_mut();
+    /// assert_eq!(spare.len(), 3);
+    /// for (i, elem) in spare.iter_mut().take(2).enumerate() {
+    ///     elem.write(i as u32 + 2);
+    /// }
+    /// // SAFETY: The two elements after the initial one were just written.
+    /// unsafe { v.set_len(3) };
+    ///
+    /// assert_eq!(v.as_ref(), &[1, 2, 3]);
+    /// ```
+    pub fn spare_capacity_mut(&mut self) -> &mut [MaybeUninit<T>] {
+        &mut self.array[self.len..]
+    }
+
+    /// Sets the length of the vector to `new_len`.
+    ///
+    /// Elements are neither initialized nor dropped by this, use [`ArrayVec::spare_capacity_mut`]
+    /// or [`ArrayVec::as_mut_ptr`] to initialize new elements first. Shrinking the vector leaks
+    /// the elements that are cut off.
+    ///
+    /// # Safety
+    ///
+    /// - `new_len` must be less than or equal to `N`.
+    /// - All elements at index < `new_len` must be initialized.
+    pub unsafe fn set_len(&mut self, new_len: usize) {
+        // INVARIANT: Guaranteed by the safety requirements.
+        self.len = new_len;
+    }
+
+    /// Consumes the vector and returns its backing storage and length, e.g. to hand the buffer
+    /// to C.
+    ///
+    /// This is the inverse of filling the vector through [`ArrayVec::spare_capacity_mut`] and
+    /// [`ArrayVec::set_len`]. The first `len` elements of the returned array are initialized and
+    /// no longer dropped by the vector, so the caller becomes responsible for running their
+    /// destructors, if any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use core::sync::atomic::{AtomicUsize, Ordering};
+    /// use kernel::arrayvec::ArrayVec;
+    ///
+    /// static DROPS: AtomicUsize = AtomicUsize::new(0);
+    ///
+    /// struct Elem(u32);
+    ///
+    /// impl Drop for Elem {
+    ///     fn drop(&mut self) {
+    ///         DROPS.fetch_add(1, Ordering::Relaxed);
+    ///     }
+    /// }
+    ///
+    /// let mut v = ArrayVec::<4, Elem>::default();
+    /// v.push(Elem(1));
+    /// v.push(Elem(2));
+    ///
+    /// let (mut array, len) = v.into_inner();
+    /// assert_eq!(len, 2);
+    /// assert_eq!(DROPS.load(Ordering::Relaxed), 0);
+    ///
+    /// for elem in &mut array[..len] {
+    ///     // SAFETY: The first `len` elements are initialized and dropped only once.
+    ///     unsafe { elem.assume_init_drop() };
+    /// }
+    /// assert_eq!(DROPS.load(Ordering::Relaxed), 2);
+    /// ```
+    pub fn into_inner(self) -> ([MaybeUninit<T>; N], usize) {
+        // The elements are moved out with the array, so `self` must not drop them.
+        let this = core::mem::ManuallyDrop::new(self);
+        // SAFETY: `this.array` is valid for reads and never used again, as `this` is not dropped.
+        let array = unsafe { core::ptr::read(&this.array) };
+        (array, this.len)
+    }
+
+    /// Appends the elements of `iter` until it is exhausted.
+    ///
+    /// If the vector runs out of capacity, the first element that does not fit is returned and the
+    /// rest of `iter` is left unconsumed. The elements pushed before are kept.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kernel::arrayvec::ArrayVec;
+    ///
+    /// let mut v = ArrayVec::<4, u32>::default();
+    /// v.push(1);
+    /// assert_eq!(v.try_extend([2, 3]), Ok(()));
+    /// assert_eq!(v.as_ref(), &[1, 2, 3]);
+    ///
+    /// assert_eq!(v.try_extend(4..8), Err(5));
+    /// assert_eq!(v.as_ref(), &[1, 2, 3, 4]);
+    /// ```
+    pub fn try_extend<I: IntoIterator<Item = T>>(&mut self, iter: I) -> Result<(), T> {
+        for elem in iter {
+            if self.len == N {
+                return Err(elem);
+            }
+            self.push(elem);
+        }
+        Ok(())
+    }
+
+    /// Overwrites every initialized element with values returned by calling `f`.
+    ///
+    /// The length of the vector is not changed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kernel::arrayvec::ArrayVec;
+    ///
+    /// let mut v = ArrayVec::<4, u32>::default();
+    /// v.push(1);
+    /// v.push(2);
+    ///
+    /// let mut next = 10;
+    /// v.fill_with(|| {
+    ///     next += 1;
+    ///     next
+    /// });
+    /// assert_eq!(v.as_ref(), &[11, 12]);
+    /// ```
+    pub fn fill_with<F: FnMut() -> T>(&mut self, f: F) {
+        self.as_mut().fill_with(f)
+    }
+
+    /// Consumes the vector and returns a new one with `f` applied to each element.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kernel::arrayvec::ArrayVec;
+    ///
+    /// let mut v = ArrayVec::<3, u32>::default();
+    /// v.push(1);
+    /// v.push(2);
+    /// v.push(3);
+    /// let v = v.map(|x| x * 10);
+    /// assert_eq!(v.as_ref(), &[10, 20, 30]);
+    ///
+    /// let mut v = ArrayVec::<4, u32>::default();
+    /// v.push(1);
+    /// let v = v.map(|x| x == 1);
+    /// assert_eq!(v.as_ref(), &[true]);
+    /// ```
+    pub fn map<U, F: FnMut(T) -> U>(self, mut f: F) -> ArrayVec<N, U> {
+        match self.try_map(|elem| Ok::<_, core::convert::Infallible>(f(elem))) {
+            Ok(res) => res,
+            Err(e) => match e {},
+        }
+    }
+
+    /// Consumes the vector and returns a new one with `f` applied to each element, stopping at
+    /// the first error.
+    ///
+    /// On error, the elements that were already mapped and the ones that were not yet passed to
+    /// `f` are dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kernel::arrayvec::ArrayVec;
+    ///
+    /// let mut v = ArrayVec::<2, u32>::default();
+    /// v.push(0x40);
+    /// v.push(0x41);
+    /// let v = v.try_map(u8::try_from).unwrap();
+    /// assert_eq!(v.as_ref(), &[0x40, 0x41]);
+    ///
+    /// let mut v = ArrayVec::<4, u32>::default();
+    /// v.push(0x40);
+    /// v.push(0x400);
+    /// v.push(0x41);
+    /// assert!(v.try_map(u8::try_from).is_err());
+    /// ```
+    pub fn try_map<U, E, F: FnMut(T) -> Result<U, E>>(self, mut f: F) -> Result<ArrayVec<N, U>, E> {
+        // The elements are moved out one by one below, so `self` must not drop them again.
+        let mut this = core::mem::ManuallyDrop::new(self);
+        let len = this.len;
+        let mut res = ArrayVec::default();
+
+        for i in 0..len {
+            // SAFETY: As per the type invariant, all elements at index < len are initialized.
+            // Each of them is read exactly once.
+            let elem = unsafe { this.array[i].assume_init_read() };
+            match f(elem) {
+                Ok(elem) => res.push(elem),
+                Err(e) => {
+                    for elem in &mut this.array[i + 1..len] {
+                        // SAFETY: The elements at index > i have not been read yet and are
+                        // initialized as above.
+                        unsafe { elem.assume_init_drop() };
+                    }
+                    return Err(e);
+                }
+            }
+        }
+
+        Ok(res)
+    }
+}
+
+impl<const N: usize, T: Clone> ArrayVec<N, T> {
+    /// Overwrites every initialized element with a clone of `value`.
+    ///
+    /// The length of the vector is not changed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kernel::arrayvec::ArrayVec;
+    ///
+    /// let mut v = ArrayVec::<4, u32>::default();
+    /// v.push(1);
+    /// v.push(2);
+    ///
+    /// v.fill(0);
+    /// assert_eq!(v.as_ref(), &[0, 0]);
+    /// assert_eq!(v.len(), 2);
+    /// ```
+    pub fn fill(&mut self, value: T) {
+        self.as_mut().fill(value)
+    }
+}
+
+impl<const N: usize, T: PartialEq> ArrayVec<N, T> {
+    /// Returns `true` if the vector contains an element equal to `x`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kernel::arrayvec::ArrayVec;
+    ///
+    /// let mut aliases = ArrayVec::<8, u8>::default();
+    /// aliases.push(0x44);
+    /// aliases.push(0x40);
+    ///
+    /// assert!(aliases.contains(&0x40));
+    /// assert!(!aliases.contains(&0x42));
+    /// ```
+    pub fn contains(&self, x: &T) -> bool {
+        self.as_ref().contains(x)
+    }
+}
+
+impl<const N: usize, T: Ord> ArrayVec<N, T> {
+    /// Binary searches the (sorted) vector for `x`.
+    ///
+    /// See [`slice::binary_search`] for the meaning of the return value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kernel::arrayvec::ArrayVec;
+    ///
+    /// let mut aliases = ArrayVec::<8, u8>::default();
+    /// aliases.push(0x40);
+    /// aliases.push(0x44);
+    /// aliases.push(0x48);
+    ///
+    /// assert_eq!(aliases.binary_search(&0x44), Ok(1));
+    /// assert_eq!(aliases.binary_search(&0x46), Err(2));
+    /// ```
+    pub fn binary_search(&self, x: &T) -> Result<usize, usize> {
+        self.as_ref().binary_search(x)
+    }
 }
 
 impl<const N: usize, T> Default for ArrayVec<N, T> {
@@ -1,6 +1,6 @@
 // SPDX-License-Identifier: GPL-2.0
 
-//! Bit manipulation macros.
+//! Bit manipulation macros and helpers.
 //!
 //! C header: [`include/linux/bits.h`](srctree/include/linux/bits.h)
 
@@ -34,3 +34,51 @@ macro_rules! genmask {
         ((!0u64 - (1u64 << $l) + 1) & (!0u64 >> (64 - 1 - $h)))
     }};
 }
+
+/// Returns an iterator over the indices of the bits set in `word`, from lowest to highest.
+///
+/// This is the Rust counterpart of the kernel's `for_each_set_bit` for a single word, handy for
+/// decoding status registers where every set bit is a separate cause.
+///
+/// # Examples
+/// ```
+///     use kernel::bits::iter_set;
+///     let mut causes = iter_set(0b1000_0101);
+///     assert_eq!(causes.next(), Some(0));
+///     assert_eq!(causes.next(), Some(2));
+///     assert_eq!(causes.next(), Some(7));
+///     assert_eq!(causes.next(), None);
+/// ```
+pub fn iter_set(mut word: u32) -> impl Iterator<Item = u32> {
+    core::iter::from_fn(move || {
+        if word == 0 {
+            return None;
+        }
+        let bit = word.trailing_zeros();
+        // Clear the lowest set bit.
+        word &= word - 1;
+        Some(bit)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_iter_set_zero() {
+        assert_eq!(iter_set(0).next(), None);
+    }
+
+    #[test]
+    fn test_iter_set_single_bit() {
+        for n in 0..u32::BITS {
+            assert!(iter_set(1 << n).eq([n]));
+        }
+    }
+
+    #[test]
+    fn test_iter_set_all_bits() {
+        assert!(iter_set(u32::MAX).eq(0..u32::BITS));
+    }
+}
@@ -5,9 +5,252 @@
 //!
 //! C header: [`include/linux/delay.h`](srctree/include/linux/delay.h).
 
-use crate::bindings;
+use crate::{
+    bindings,
+    error::{code::ETIMEDOUT, Result},
+    time::Ktime,
+};
 
 pub fn msleep(msecs: u32) {
     // SAFETY: The behavior of msleep it defined for the full range of `u32`.
     unsafe { bindings::msleep(msecs) }
 }
+
+/// Sleeps for `min_us` to `max_us` microseconds, letting the timer subsystem coalesce wakeups.
+///
+/// Meant for delays of up to a few milliseconds, use [`msleep`] for longer ones.
+pub fn usleep_range(min_us: u64, max_us: u64) {
+    // SAFETY: `usleep_range_state` is defined for any range, an empty one just sleeps `min_us`.
+    unsafe {
+        bindings::usleep_range_state(min_us as _, max_us as _, bindings::TASK_UNINTERRUPTIBLE)
+    }
+}
+
+/// Source of time for the polling helpers.
+///
+/// This only exists so unit tests can drive the polling helpers with virtual time, everything
+/// else uses [`KernelClock`].
+pub(crate) trait Clock {
+    /// Returns the current monotonic time in milliseconds.
+    fn now_ms(&self) -> i64;
+
+    /// Sleeps for at least `msecs` milliseconds.
+    fn sleep_ms(&self, msecs: u32);
+
+    /// Returns the current monotonic time in microseconds.
+    fn now_us(&self) -> i64;
+
+    /// Sleeps for about `usecs` microseconds.
+    fn sleep_us(&self, usecs: u64);
+}
+
+/// The kernel's `CLOCK_MONOTONIC`, [`msleep`] and [`usleep_range`].
+pub(crate) struct KernelClock;
+
+impl Clock for KernelClock {
+    fn now_ms(&self) -> i64 {
+        Ktime::ktime_get().to_ms()
+    }
+
+    fn sleep_ms(&self, msecs: u32) {
+        msleep(msecs)
+    }
+
+    fn now_us(&self) -> i64 {
+        Ktime::ktime_get().to_us()
+    }
+
+    fn sleep_us(&self, usecs: u64) {
+        // Same range as the C `read_poll_timeout`.
+        usleep_range((usecs >> 2) + 1, usecs)
+    }
+}
+
+/// Calls `op` until `cond` is true for the value it returns or `timeout_ms` milliseconds passed.
+///
+/// Sleeps `sleep_ms` milliseconds between the calls. Like the C `read_poll_timeout`, `op` is
+/// called once more after the timeout expired, so a slow sleep can't cause a spurious timeout.
+/// Errors returned by `op` are passed on immediately.
+///
+/// Returns the first value satisfying `cond`, or [`ETIMEDOUT`].
+///
+/// # Examples
+///
+/// ```
+/// use kernel::delay::read_poll_timeout;
+///
+/// // Wait up to 100 ms for bit 0 of a status register to be set.
+/// fn wait_ready(read_status: impl FnMut() -> Result<u32>) -> Result<u32> {
+///     read_poll_timeout(read_status, |val| val & 1 != 0, 10, 100)
+/// }
+/// ```
+pub fn read_poll_timeout<T>(
+    op: impl FnMut() -> Result<T>,
+    cond: impl FnMut(&T) -> bool,
+    sleep_ms: u32,
+    timeout_ms: u32,
+) -> Result<T> {
+    read_poll_timeout_with(&KernelClock, op, cond, sleep_ms, timeout_ms)
+}
+
+/// Waits until `cond` returns true or `timeout_ms` milliseconds passed.
+///
+/// See [`read_poll_timeout`] for details.
+pub fn poll_timeout(mut cond: impl FnMut() -> bool, sleep_ms: u32, timeout_ms: u32) -> Result {
+    read_poll_timeout(|| Ok(cond()), |&done| done, sleep_ms, timeout_ms).map(|_| ())
+}
+
+pub(crate) fn read_poll_timeout_with<C: Clock, T>(
+    clock: &C,
+    mut op: impl FnMut() -> Result<T>,
+    mut cond: impl FnMut(&T) -> bool,
+    sleep_ms: u32,
+    timeout_ms: u32,
+) -> Result<T> {
+    let deadline = clock.now_ms() + i64::from(timeout_ms);
+    loop {
+        let timed_out = clock.now_ms() >= deadline;
+        let val = op()?;
+        if cond(&val) {
+            return Ok(val);
+        }
+        if timed_out {
+            return Err(ETIMEDOUT);
+        }
+        if sleep_ms != 0 {
+            clock.sleep_ms(sleep_ms);
+        }
+    }
+}
+
+/// Like [`read_poll_timeout_with`], but sleeping `sleep_us` and timing out after `timeout_us`
+/// microseconds.
+pub(crate) fn read_poll_timeout_us_with<C: Clock, T>(
+    clock: &C,
+    mut op: impl FnMut() -> Result<T>,
+    mut cond: impl FnMut(&T) -> bool,
+    sleep_us: u64,
+    timeout_us: u64,
+) -> Result<T> {
+    let deadline = clock
+        .now_us()
+        .saturating_add(i64::try_from(timeout_us).unwrap_or(i64::MAX));
+    loop {
+        let timed_out = clock.now_us() >= deadline;
+        let val = op()?;
+        if cond(&val) {
+            return Ok(val);
+        }
+        if timed_out {
+            return Err(ETIMEDOUT);
+        }
+        if sleep_us != 0 {
+            clock.sleep_us(sleep_us);
+        }
+    }
+}
+
+/// Converts microseconds to jiffies, rounding up.
+///
+/// Values that don't fit the C helper's `unsigned int` argument saturate.
+///
+/// # Examples
+///
+/// ```
+/// use kernel::{bindings, delay::usecs_to_jiffies};
+///
+/// assert_eq!(usecs_to_jiffies(1_000_000), u64::from(bindings::HZ));
+/// assert_eq!(usecs_to_jiffies(0), 0);
+/// assert!(usecs_to_jiffies(1) >= 1);
+/// ```
+pub fn usecs_to_jiffies(usecs: u64) -> u64 {
+    let usecs = u32::try_from(usecs).unwrap_or(u32::MAX);
+    // SAFETY: `__usecs_to_jiffies` is defined for the full range of `u32`.
+    unsafe { bindings::__usecs_to_jiffies(usecs) as u64 }
+}
+
+/// Converts jiffies to milliseconds.
+///
+/// # Examples
+///
+/// ```
+/// use kernel::{bindings, delay::jiffies_to_msecs};
+///
+/// assert_eq!(jiffies_to_msecs(u64::from(bindings::HZ)), 1000);
+/// assert_eq!(jiffies_to_msecs(0), 0);
+/// ```
+pub fn jiffies_to_msecs(j: u64) -> u64 {
+    // SAFETY: `jiffies_to_msecs` is defined for the full range of `unsigned long`.
+    unsafe { bindings::jiffies_to_msecs(j as _) as u64 }
+}
+
+/// Virtual time in microseconds that only advances when sleeping.
+#[cfg(test)]
+pub(crate) struct MockClock(pub(crate) core::cell::Cell<i64>);
+
+#[cfg(test)]
+impl Clock for MockClock {
+    fn now_ms(&self) -> i64 {
+        self.0.get() / 1000
+    }
+
+    fn sleep_ms(&self, msecs: u32) {
+        self.sleep_us(u64::from(msecs) * 1000);
+    }
+
+    fn now_us(&self) -> i64 {
+        self.0.get()
+    }
+
+    fn sleep_us(&self, usecs: u64) {
+        self.0.set(self.0.get() + usecs as i64);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::cell::Cell;
+
+    #[test]
+    fn test_read_poll_timeout_expires() {
+        let clock = MockClock(Cell::new(0));
+        let mut calls = 0;
+
+        let res = read_poll_timeout_with(
+            &clock,
+            || {
+                calls += 1;
+                Ok(0)
+            },
+            |&val| val != 0,
+            10,
+            100,
+        );
+
+        assert_eq!(res, Err(ETIMEDOUT));
+        assert_eq!(clock.now_ms(), 100);
+        // One call at each multiple of 10 ms, including the last one at the deadline.
+        assert_eq!(calls, 11);
+    }
+
+    #[test]
+    fn test_read_poll_timeout_succeeds() {
+        let clock = MockClock(Cell::new(0));
+        let mut val = 0;
+
+        let res = read_poll_timeout_with(
+            &clock,
+            || {
+                val += 1;
+                Ok(val)
+            },
+            |&val| val == 3,
+            10,
+            100,
+        );
+
+        assert_eq!(res, Ok(3));
+        assert_eq!(clock.now_ms(), 20);
+    }
+}
@@ -7,7 +7,7 @@
 use crate::{
     bindings,
     fwnode::FwNode,
-    types::{ARef, Opaque},
+    types::{ARef, ForeignOwnable, Opaque},
 };
 use core::{fmt, ptr};
 
@@ -194,6 +194,137 @@ pub fn as_fwnode(&self) -> &FwNode {
         // doesn't increment the refcount.
         unsafe { &*fwnode_handle.cast() }
     }
+
+    /// Returns firmware property `name` of the device as a 64-bit value, checking its size.
+    ///
+    /// See [`FwNode::property_read_u64_checked`].
+    pub fn property_read_u64_checked(&self, name: &crate::str::CStr) -> crate::error::Result<u64> {
+        self.as_fwnode().property_read_u64_checked(name)
+    }
+
+    /// Returns firmware property `name` of the device converted to `T`.
+    ///
+    /// See [`FwNode::property_read_enum`].
+    pub fn property_read_enum<T: crate::fwnode::FromProperty>(
+        &self,
+        name: &crate::str::CStr,
+    ) -> crate::error::Result<T> {
+        self.as_fwnode().property_read_enum(name)
+    }
+
+    /// Returns firmware property `name` of the device as an array of exactly `N` integers.
+    ///
+    /// Like [`FwNode::property_read_array`], but if the property exists with fewer than `N`
+    /// elements, an error naming the expected and the actual element count is logged. This also
+    /// happens when falling back to `default`, as the property is malformed either way.
+    pub fn property_read_array<T: crate::types::Integer, const N: usize>(
+        &self,
+        name: &crate::str::CStr,
+        default: Option<[T; N]>,
+    ) -> crate::error::Result<[T; N]> {
+        let fwnode = self.as_fwnode();
+        fwnode.property_read_array(name, None).or_else(|err| {
+            if let Ok(found) = fwnode.property_count_elem::<T>(name) {
+                if found < N {
+                    let mismatch = crate::fwnode::ArrayLenMismatch {
+                        name,
+                        expected: N,
+                        found,
+                    };
+                    crate::dev_err!(self, "{mismatch}\n");
+                }
+            }
+            default.ok_or(err)
+        })
+    }
+
+    /// Returns up to `N` values of firmware property `name` of the device in an
+    /// [`ArrayVec`](crate::arrayvec::ArrayVec).
+    ///
+    /// Longer properties are silently truncated, see [`FwNode::property_read_arrayvec`].
+    pub fn property_read_arrayvec<T: crate::types::Integer, const N: usize>(
+        &self,
+        name: &crate::str::CStr,
+    ) -> crate::error::Result<crate::arrayvec::ArrayVec<N, T>> {
+        self.as_fwnode().property_read_arrayvec(name)
+    }
+
+    /// Stores `data` as the driver data of the device.
+    ///
+    /// The device takes ownership of `data` until it is reclaimed with [`Device::take_drvdata`].
+    /// Any driver data that was stored before is overwritten without being dropped.
+    ///
+    /// # Safety
+    ///
+    /// The driver data slot must not be in use by anyone else, in particular not by a bus
+    /// abstraction (e.g. [`i2c::Adapter`] stores the driver's private data there). All subsequent
+    /// calls to [`Device::get_drvdata`] and [`Device::take_drvdata`] must use the same type `T`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kernel::device::Device;
+    ///
+    /// fn round_trip(dev: &Device) -> Result {
+    ///     // SAFETY: The driver data of `dev` is not used by anyone else, and it is only ever
+    ///     // accessed as `KBox<u32>`.
+    ///     unsafe { dev.set_drvdata(KBox::new(42u32, GFP_KERNEL)?) };
+    ///
+    ///     // SAFETY: As above.
+    ///     assert_eq!(unsafe { dev.get_drvdata::<KBox<u32>>() }.copied(), Some(42));
+    ///
+    ///     // SAFETY: As above.
+    ///     let data = unsafe { dev.take_drvdata::<KBox<u32>>() };
+    ///     assert_eq!(data.as_deref(), Some(&42));
+    ///
+    ///     // SAFETY: As above.
+    ///     assert!(unsafe { dev.get_drvdata::<KBox<u32>>() }.is_none());
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// [`i2c::Adapter`]: crate::i2c::Adapter
+    pub unsafe fn set_drvdata<T: ForeignOwnable>(&self, data: T) {
+        // SAFETY: By the type invariants `self.as_raw()` is a valid pointer to a `struct device`.
+        unsafe { bindings::dev_set_drvdata(self.as_raw(), data.into_foreign() as _) };
+    }
+
+    /// Borrows the driver data of the device, if any.
+    ///
+    /// # Safety
+    ///
+    /// If driver data is set, it must have been stored with [`Device::set_drvdata`] using the same
+    /// type `T`, and it must not be reclaimed with [`Device::take_drvdata`] while the returned
+    /// borrow is alive.
+    pub unsafe fn get_drvdata<T: ForeignOwnable>(&self) -> Option<T::Borrowed<'_>> {
+        // SAFETY: By the type invariants `self.as_raw()` is a valid pointer to a `struct device`.
+        let ptr = unsafe { bindings::dev_get_drvdata(self.as_raw()) };
+        if ptr.is_null() {
+            return None;
+        }
+
+        // SAFETY: By the safety requirements `ptr` was returned by `T::into_foreign` and has not
+        // been passed to `T::from_foreign` yet.
+        Some(unsafe { T::borrow(ptr) })
+    }
+
+    /// Reclaims ownership of the driver data of the device, if any, and clears it.
+    ///
+    /// # Safety
+    ///
+    /// If driver data is set, it must have been stored with [`Device::set_drvdata`] using the same
+    /// type `T`, and no borrow obtained with [`Device::get_drvdata`] may be alive.
+    pub unsafe fn take_drvdata<T: ForeignOwnable>(&self) -> Option<T> {
+        // SAFETY: By the type invariants `self.as_raw()` is a valid pointer to a `struct device`.
+        let ptr = unsafe { bindings::dev_get_drvdata(self.as_raw()) };
+        // SAFETY: By the type invariants `self.as_raw()` is a valid pointer to a `struct device`.
+        unsafe { bindings::dev_set_drvdata(self.as_raw(), ptr::null_mut()) };
+
+        // SAFETY: By the safety requirements `ptr` is either null or was returned by
+        // `T::into_foreign`, and ownership is transferred back exactly once since the slot has
+        // been cleared above.
+        unsafe { T::try_from_foreign(ptr) }
+    }
 }
 
 // SAFETY: Instances of `Device` are always reference-counted.
@@ -63,6 +63,8 @@ macro_rules! declare_err {
     declare_err!(EPIPE, "Broken pipe.");
     declare_err!(EDOM, "Math argument out of domain of func.");
     declare_err!(ERANGE, "Math result not representable.");
+    declare_err!(EOVERFLOW, "Value too large for defined data type.");
+    declare_err!(ETIMEDOUT, "Connection timed out.");
     declare_err!(ERESTARTSYS, "Restart the system call.");
     declare_err!(ERESTARTNOINTR, "System call was interrupted by a signal and will be restarted.");
     declare_err!(ERESTARTNOHAND, "Restart if no handler.");
@@ -156,6 +158,19 @@ pub fn to_ptr<T>(self) -> *mut T {
     }
 
     /// Returns a string representing the error, if one exists.
+    ///
+    /// This is the symbolic name of the errno, as printed by the `%pe` format specifier.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let name = |err: Error| err.name().map(|name| name.to_str());
+    ///
+    /// assert_eq!(name(EIO), Some(Ok("EIO")));
+    /// assert_eq!(name(EINVAL), Some(Ok("EINVAL")));
+    /// assert_eq!(name(ENODEV), Some(Ok("ENODEV")));
+    /// assert_eq!(name(EPROBE_DEFER), Some(Ok("EPROBE_DEFER")));
+    /// ```
     #[cfg(not(any(test, testlib)))]
     pub fn name(&self) -> Option<&'static CStr> {
         // SAFETY: Just an FFI call, there are no extra safety requirements.
@@ -194,6 +209,17 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
     }
 }
 
+/// Prints the symbolic name of the error, e.g. `EIO`, or its number if it has no name.
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.name() {
+            None => write!(f, "error {}", self.0),
+            // SAFETY: These strings are ASCII-only.
+            Some(name) => f.write_str(unsafe { core::str::from_utf8_unchecked(name) }),
+        }
+    }
+}
+
 impl From<AllocError> for Error {
     fn from(_: AllocError) -> Error {
         code::ENOMEM
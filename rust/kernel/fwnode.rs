@@ -43,6 +43,60 @@ pub(crate) fn as_raw(&self) -> *mut bindings::fwnode_handle {
         self.0.get()
     }
 
+    /// Returns if the device described by the fwnode is available
+    ///
+    /// This is the case unless the node has e.g. `status = "disabled"` in the device tree.
+    /// Software nodes are always available.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(CONFIG_OF_DYNAMIC)]
+    /// # {
+    /// use kernel::{bindings, c_str, error::to_result, fwnode::FwNode};
+    ///
+    /// let mut ocs = core::mem::MaybeUninit::<bindings::of_changeset>::uninit();
+    /// let ocs = ocs.as_mut_ptr();
+    /// // SAFETY: `ocs` is initialized before it is used, and the nodes it creates stay valid
+    /// // until it is destroyed.
+    /// unsafe {
+    ///     bindings::of_changeset_init(ocs);
+    ///     let okay = bindings::of_changeset_create_node(
+    ///         ocs,
+    ///         bindings::of_root,
+    ///         c_str!("rust-fwnode-okay").as_char_ptr(),
+    ///     );
+    ///     let disabled = bindings::of_changeset_create_node(
+    ///         ocs,
+    ///         bindings::of_root,
+    ///         c_str!("rust-fwnode-disabled").as_char_ptr(),
+    ///     );
+    ///     assert!(!okay.is_null() && !disabled.is_null());
+    ///     to_result(bindings::of_changeset_add_prop_string(
+    ///         ocs,
+    ///         disabled,
+    ///         c_str!("status").as_char_ptr(),
+    ///         c_str!("disabled").as_char_ptr(),
+    ///     ))
+    ///     .unwrap();
+    ///     to_result(bindings::of_changeset_apply(ocs)).unwrap();
+    ///
+    ///     let fwnode = |np: *mut bindings::device_node| {
+    ///         &*core::ptr::addr_of_mut!((*np).fwnode).cast::<FwNode>()
+    ///     };
+    ///     assert!(fwnode(okay).is_available());
+    ///     assert!(!fwnode(disabled).is_available());
+    ///
+    ///     to_result(bindings::of_changeset_revert(ocs)).unwrap();
+    ///     bindings::of_changeset_destroy(ocs);
+    /// }
+    /// # }
+    /// ```
+    pub fn is_available(&self) -> bool {
+        // SAFETY: `self.as_raw` is valid because `self` is valid.
+        unsafe { bindings::fwnode_device_is_available(self.as_raw()) }
+    }
+
     /// Returns if a firmware property `name` is present
     pub fn property_present(&self, name: &CStr) -> bool {
         // SAFETY: `name` is non-null and null-terminated. `self.as_raw` is valid
@@ -102,6 +156,13 @@ pub fn property_read<T: Integer>(&self, name: &CStr, default: Option<T>) -> Resu
         Ok(val[0])
     }
 
+    /// Returns firmware property `name` integer scalar value converted to `T`
+    ///
+    /// Returns [`EINVAL`] if the value doesn't correspond to any `T`.
+    pub fn property_read_enum<T: FromProperty>(&self, name: &CStr) -> Result<T> {
+        enum_from_property(self.property_read::<u32>(name, None)?)
+    }
+
     /// Returns firmware property `name` integer array values
     pub fn property_read_array<T: Integer, const N: usize>(
         &self,
@@ -155,6 +216,45 @@ pub fn property_read_array_vec<T: Integer>(&self, name: &CStr, len: usize) -> Re
         Ok(val)
     }
 
+    /// Returns up to `N` firmware property `name` integer array values in an [`ArrayVec`]
+    ///
+    /// Unlike [`FwNode::property_read_array`], the property may be shorter than `N`, and unlike
+    /// [`FwNode::property_read_array_vec`], nothing is allocated. Longer properties are silently
+    /// truncated to their first `N` values, use [`FwNode::property_count_elem`] to detect that.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kernel::{arrayvec::ArrayVec, c_str, fwnode::FwNode};
+    ///
+    /// // For `gpio-pulldown-disable = <1 3>;` this returns `[1, 3]`, for
+    /// // `gpio-pulldown-disable = <0 1 2 3>;` it returns all four values.
+    /// fn pulldown_disable(node: &FwNode) -> Result<ArrayVec<4, u32>> {
+    ///     node.property_read_arrayvec(c_str!("gpio-pulldown-disable"))
+    /// }
+    /// ```
+    pub fn property_read_arrayvec<T: Integer, const N: usize>(
+        &self,
+        name: &CStr,
+    ) -> Result<ArrayVec<N, T>> {
+        let count = self.property_count_elem::<T>(name)?;
+        let read = |elems: &mut [MaybeUninit<T>]| {
+            // SAFETY: `name` is non-null and null-terminated. `self.as_raw` is valid
+            // because `self` is valid. `elems` is valid for writing `elems.len()` entries.
+            to_result(unsafe {
+                bindings::fwnode_property_read_int_array(
+                    self.as_raw(),
+                    name.as_ptr() as *const u8,
+                    T::SIZE.try_into().unwrap(),
+                    elems.as_mut_ptr() as *mut c_void,
+                    elems.len(),
+                )
+            })
+        };
+        // SAFETY: fwnode_property_read_int_array() writes all `elems.len()` entries on success.
+        unsafe { arrayvec_from_elems(count, read) }
+    }
+
     /// Returns integer array length for firmware property `name`
     pub fn property_count_elem<T: Integer>(&self, name: &CStr) -> Result<usize> {
         // SAFETY: `name` is non-null and null-terminated. `self.as_raw` is valid
@@ -173,6 +273,30 @@ pub fn property_count_elem<T: Integer>(&self, name: &CStr) -> Result<usize> {
         Ok(ret.try_into().unwrap())
     }
 
+    /// Returns firmware property `name` as a 64-bit value, checking its size
+    ///
+    /// Device trees store 64-bit values in two cells. Unlike `property_read::<u64>`, which
+    /// silently ignores trailing cells, this returns [`EOVERFLOW`] unless the property is exactly
+    /// two cells long.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kernel::{c_str, fwnode::FwNode};
+    ///
+    /// // For `clock-frequency-hz = <0x1 0x00000000>;` this returns `Ok(0x1_0000_0000)`, for
+    /// // `clock-frequency-hz = <0x1>;` and `clock-frequency-hz = <0x0 0x1 0x0>;` it returns
+    /// // `Err(EOVERFLOW)`.
+    /// fn clock_frequency(node: &FwNode) -> Result<u64> {
+    ///     node.property_read_u64_checked(c_str!("clock-frequency-hz"))
+    /// }
+    /// ```
+    pub fn property_read_u64_checked(&self, name: &CStr) -> Result<u64> {
+        u64_from_cells(self.property_count_elem::<u32>(name)?, || {
+            self.property_read::<u64>(name, None)
+        })
+    }
+
     // SAFETY: `raw` must have its refcount incremented.
     unsafe fn from_raw(raw: *mut bindings::fwnode_handle) -> ARef<Self> {
         unsafe { ARef::from_raw(NonNull::new_unchecked(raw.cast())) }
@@ -268,6 +392,97 @@ pub fn property_get_reference_args(
     }
 }
 
+/// Types that a firmware property holding a single `u32` can be read as, typically enums of
+/// modes or speeds.
+///
+/// # Examples
+///
+/// ```
+/// use kernel::{c_str, device::Device, fwnode::FromProperty};
+///
+/// #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// enum LaneSpeed {
+///     Mbps400,
+///     Mbps800,
+///     Mbps1600,
+/// }
+///
+/// impl FromProperty for LaneSpeed {
+///     fn from_property(value: u32) -> Option<Self> {
+///         match value {
+///             400 => Some(Self::Mbps400),
+///             800 => Some(Self::Mbps800),
+///             1600 => Some(Self::Mbps1600),
+///             _ => None,
+///         }
+///     }
+/// }
+///
+/// fn lane_speed(dev: &Device) -> LaneSpeed {
+///     dev.property_read_enum(c_str!("lane-speed"))
+///         .unwrap_or(LaneSpeed::Mbps1600)
+/// }
+/// ```
+pub trait FromProperty: Sized {
+    /// Converts the property value `value`, or returns `None` if it is unknown.
+    fn from_property(value: u32) -> Option<Self>;
+}
+
+fn enum_from_property<T: FromProperty>(value: u32) -> Result<T> {
+    T::from_property(value).ok_or(EINVAL)
+}
+
+/// Reads the first `N` of the `count` elements of a property into an [`ArrayVec`] through
+/// `read`, which isn't called if there is nothing to read.
+///
+/// # Safety
+///
+/// On success, `read` must have initialized all elements of the slice it is passed.
+unsafe fn arrayvec_from_elems<T, const N: usize>(
+    count: usize,
+    read: impl FnOnce(&mut [MaybeUninit<T>]) -> Result,
+) -> Result<ArrayVec<N, T>> {
+    let len = count.min(N);
+    let mut val = ArrayVec::default();
+    if len == 0 {
+        return Ok(val);
+    }
+
+    read(&mut val.spare_capacity_mut()[..len])?;
+    // SAFETY: By the safety requirements, `read` initialized the first `len <= N` elements.
+    unsafe { val.set_len(len) };
+    Ok(val)
+}
+
+/// Reads a 64-bit property with `cells` 32-bit cells through `read`, if it has exactly two.
+fn u64_from_cells(cells: usize, read: impl FnOnce() -> Result<u64>) -> Result<u64> {
+    if cells != 2 {
+        return Err(EOVERFLOW);
+    }
+    read()
+}
+
+/// Describes an integer array property with fewer elements than expected.
+///
+/// Used by [`Device::property_read_array`] to report why reading the property failed.
+///
+/// [`Device::property_read_array`]: crate::device::Device::property_read_array
+pub(crate) struct ArrayLenMismatch<'a> {
+    pub(crate) name: &'a CStr,
+    pub(crate) expected: usize,
+    pub(crate) found: usize,
+}
+
+impl core::fmt::Display for ArrayLenMismatch<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "{} has {} elements, expected {}",
+            self.name, self.found, self.expected
+        )
+    }
+}
+
 pub enum NArgs<'a> {
     Prop(&'a CStr),
     N(u32),
@@ -285,3 +500,89 @@ unsafe fn dec_ref(obj: ptr::NonNull<Self>) {
         unsafe { bindings::fwnode_handle_put(obj.cast().as_ptr()) }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::c_str;
+
+    #[derive(Debug, PartialEq)]
+    enum Mode {
+        Off,
+        On,
+    }
+
+    impl FromProperty for Mode {
+        fn from_property(value: u32) -> Option<Self> {
+            match value {
+                0 => Some(Self::Off),
+                1 => Some(Self::On),
+                _ => None,
+            }
+        }
+    }
+
+    #[test]
+    fn test_enum_from_property() {
+        assert_eq!(enum_from_property::<Mode>(0), Ok(Mode::Off));
+        assert_eq!(enum_from_property::<Mode>(1), Ok(Mode::On));
+        assert_eq!(enum_from_property::<Mode>(2), Err(EINVAL));
+        assert_eq!(enum_from_property::<Mode>(u32::MAX), Err(EINVAL));
+    }
+
+    #[test]
+    fn test_u64_from_cells() {
+        assert_eq!(u64_from_cells(2, || Ok(0x1_0000_0000)), Ok(0x1_0000_0000));
+        assert_eq!(u64_from_cells(2, || Err(EINVAL)), Err(EINVAL));
+
+        // The value is only read if the property has the right size.
+        let unread = || -> Result<u64> { panic!("read a property of the wrong size") };
+        assert_eq!(u64_from_cells(1, unread), Err(EOVERFLOW));
+        assert_eq!(u64_from_cells(3, unread), Err(EOVERFLOW));
+        assert_eq!(u64_from_cells(0, unread), Err(EOVERFLOW));
+    }
+
+    /// Fills `elems` with 1, 2, 3, ...
+    fn fill_elems(elems: &mut [MaybeUninit<u32>]) -> Result {
+        for (i, elem) in elems.iter_mut().enumerate() {
+            elem.write(i as u32 + 1);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_arrayvec_from_elems() {
+        // SAFETY: `fill_elems` initializes all elements.
+        let read = |count| unsafe { arrayvec_from_elems::<u32, 4>(count, fill_elems) };
+
+        assert_eq!(read(2).unwrap().as_ref(), [1, 2]);
+        assert_eq!(read(4).unwrap().as_ref(), [1, 2, 3, 4]);
+        // Longer properties are truncated.
+        assert_eq!(read(6).unwrap().as_ref(), [1, 2, 3, 4]);
+
+        // Empty properties aren't read.
+        let unread = |_: &mut [MaybeUninit<u32>]| -> Result { panic!("read an empty property") };
+        // SAFETY: `unread` is never called.
+        let empty = unsafe { arrayvec_from_elems::<u32, 4>(0, unread) };
+        assert!(empty.unwrap().as_ref().is_empty());
+
+        // SAFETY: `read` fails.
+        let failed = unsafe { arrayvec_from_elems::<u32, 4>(2, |_| Err(EINVAL)) };
+        assert_eq!(failed.err(), Some(EINVAL));
+    }
+
+    #[test]
+    fn test_array_len_mismatch_message() {
+        let mismatch = ArrayLenMismatch {
+            name: c_str!("csi-lane-polarities"),
+            expected: 5,
+            found: 3,
+        };
+        let msg = CString::try_from_fmt(crate::fmt!("{mismatch}")).unwrap();
+
+        assert_eq!(
+            msg.to_str().unwrap(),
+            "csi-lane-polarities has 3 elements, expected 5"
+        );
+    }
+}
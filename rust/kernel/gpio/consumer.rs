@@ -4,13 +4,14 @@
 
 use crate::{
     device::Device,
-    error::{code::*, from_err_ptr, Result},
+    error::{code::*, from_err_ptr, to_result, Result},
+    ffi::c_ulong,
     str::CStr,
 };
 use core::ptr::NonNull;
 
 /// Flags that can be passed to passed to configure direction and output value.
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 #[repr(u32)]
 pub enum Flags {
     /// Don't change anything.
@@ -27,6 +28,29 @@ pub enum Flags {
     OutHighOpenDrain = bindings::gpiod_flags_GPIOD_OUT_HIGH_OPEN_DRAIN,
 }
 
+/// Bias of a GPIO line.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Bias {
+    /// Disable any pull-up or pull-down.
+    Disable,
+    /// Pull the line up.
+    PullUp,
+    /// Pull the line down.
+    PullDown,
+}
+
+impl Bias {
+    /// Packed pin configuration for `gpiod_set_config`, see `pinconf_to_config_packed`.
+    fn to_config(self) -> crate::ffi::c_ulong {
+        let (param, arg) = match self {
+            Bias::Disable => (bindings::pin_config_param_PIN_CONFIG_BIAS_DISABLE, 0),
+            Bias::PullUp => (bindings::pin_config_param_PIN_CONFIG_BIAS_PULL_UP, 1),
+            Bias::PullDown => (bindings::pin_config_param_PIN_CONFIG_BIAS_PULL_DOWN, 1),
+        };
+        ((arg << 8) | param) as _
+    }
+}
+
 pub struct Desc(NonNull<bindings::gpio_desc>);
 
 impl Desc {
@@ -56,6 +80,164 @@ pub fn get_optional(dev: &Device, con_id: &'static CStr, flags: Flags) -> Result
         Ok(Some(Self(unsafe { NonNull::new_unchecked(desc) })))
     }
 
+    /// Obtain the GPIO at `index` of a given GPIO function.
+    ///
+    /// See [gpiod_get_index](`https://docs.kernel.org/driver-api/gpio/index.html#c.gpiod_get_index`)
+    pub fn get_index(
+        dev: &Device,
+        con_id: &'static CStr,
+        index: u32,
+        flags: Flags,
+    ) -> Result<Self> {
+        // SAFETY: `dev.as_raw()` is a valid `struct device` and `con_id` a valid C string.
+        let desc = from_err_ptr(unsafe {
+            bindings::gpiod_get_index(dev.as_raw(), con_id.as_char_ptr(), index, flags as _)
+        })?;
+
+        Ok(Self(NonNull::new(desc).ok_or(EINVAL)?))
+    }
+
+    /// Obtain the optional GPIO at `index` of a given GPIO function.
+    ///
+    /// See [gpiod_get_index_optional](`https://docs.kernel.org/driver-api/gpio/index.html#c.gpiod_get_index_optional`)
+    pub fn get_index_optional(
+        dev: &Device,
+        con_id: &'static CStr,
+        index: u32,
+        flags: Flags,
+    ) -> Result<Option<Self>> {
+        // SAFETY: `dev.as_raw()` is a valid `struct device` and `con_id` a valid C string.
+        let desc = from_err_ptr(unsafe {
+            bindings::gpiod_get_index_optional(
+                dev.as_raw(),
+                con_id.as_char_ptr(),
+                index,
+                flags as _,
+            )
+        })?;
+
+        Ok(NonNull::new(desc).map(Self))
+    }
+
+    /// Returns the raw `struct gpio_desc` pointer.
+    pub fn as_raw(&self) -> *mut bindings::gpio_desc {
+        self.0.as_ptr()
+    }
+
+    /// Consumes the descriptor and returns the raw `struct gpio_desc` pointer.
+    ///
+    /// The caller becomes responsible for releasing the GPIO with `gpiod_put`, or for handing it
+    /// to C code that does.
+    pub fn into_raw(self) -> *mut bindings::gpio_desc {
+        let desc = self.0.as_ptr();
+        core::mem::forget(self);
+        desc
+    }
+
+    /// Set the GPIO as input.
+    ///
+    /// See [gpiod_direction_input](`https://docs.kernel.org/driver-api/gpio/index.html#c.gpiod_direction_input`)
+    pub fn direction_input(&mut self) -> Result {
+        // SAFETY: Type invariants insures that `self.0` is a valid and non-null pointer, hence it
+        // is safe to perform this FFI function call.
+        to_result(unsafe { bindings::gpiod_direction_input(self.0.as_ptr()) })
+    }
+
+    /// Set the GPIO as output, driving the logical `value`.
+    ///
+    /// See [gpiod_direction_output](`https://docs.kernel.org/driver-api/gpio/index.html#c.gpiod_direction_output`)
+    pub fn direction_output(&mut self, value: i32) -> Result {
+        // SAFETY: Type invariants insures that `self.0` is a valid and non-null pointer, hence it
+        // is safe to perform this FFI function call.
+        to_result(unsafe { bindings::gpiod_direction_output(self.0.as_ptr(), value) })
+    }
+
+    /// Return the current direction of the GPIO.
+    ///
+    /// Inputs return [`Flags::In`]. Outputs return [`Flags::OutHigh`] or [`Flags::OutLow`]
+    /// depending on the logical value they drive, so the result can be passed back to
+    /// [`Desc::get`]. Open-drain outputs are reported like push-pull ones. This might sleep.
+    ///
+    /// See [gpiod_get_direction](`https://docs.kernel.org/driver-api/gpio/index.html#c.gpiod_get_direction`)
+    pub fn get_direction(&self) -> Result<Flags> {
+        // SAFETY: Type invariants insures that `self.0` is a valid and non-null pointer, hence it
+        // is safe to perform this FFI function call.
+        let ret = unsafe { bindings::gpiod_get_direction(self.0.as_ptr()) };
+        to_result(ret)?;
+        // `GPIO_LINE_DIRECTION_IN` is 1, `GPIO_LINE_DIRECTION_OUT` is 0.
+        if ret != 0 {
+            return Ok(Flags::In);
+        }
+        Ok(if self.get_value_cansleep()? {
+            Flags::OutHigh
+        } else {
+            Flags::OutLow
+        })
+    }
+
+    /// Set the debounce time of an input GPIO, in microseconds.
+    ///
+    /// Returns an error, typically [`ENOTSUPP`], if the controller can't debounce the line.
+    ///
+    /// See [gpiod_set_debounce](`https://docs.kernel.org/driver-api/gpio/index.html#c.gpiod_set_debounce`)
+    pub fn set_debounce(&mut self, usec: u32) -> Result {
+        // SAFETY: Type invariants insures that `self.0` is a valid and non-null pointer, hence it
+        // is safe to perform this FFI function call.
+        to_result(unsafe { bindings::gpiod_set_debounce(self.0.as_ptr(), usec) })
+    }
+
+    /// Set the bias of the GPIO.
+    ///
+    /// Returns an error, typically [`ENOTSUPP`], if the controller can't configure the bias.
+    ///
+    /// See [gpiod_set_config](`https://docs.kernel.org/driver-api/gpio/index.html#c.gpiod_set_config`)
+    pub fn set_bias(&mut self, bias: Bias) -> Result {
+        // SAFETY: Type invariants insures that `self.0` is a valid and non-null pointer, hence it
+        // is safe to perform this FFI function call.
+        to_result(unsafe { bindings::gpiod_set_config(self.0.as_ptr(), bias.to_config()) })
+    }
+
+    /// Return the IRQ number corresponding to the GPIO.
+    ///
+    /// Returns an error if the GPIO can't be used as an interrupt, e.g. because the controller
+    /// doesn't support it.
+    ///
+    /// See [gpiod_to_irq](`https://docs.kernel.org/driver-api/gpio/index.html#c.gpiod_to_irq`)
+    pub fn to_irq(&self) -> Result<u32> {
+        // SAFETY: Type invariants insures that `self.0` is a valid and non-null pointer, hence it
+        // is safe to perform this FFI function call.
+        let ret = unsafe { bindings::gpiod_to_irq(self.0.as_ptr()) };
+        to_result(ret)?;
+        Ok(ret as u32)
+    }
+
+    /// Read a GPIO's logical value, taking its active-low property into account.
+    ///
+    /// Unlike the C function, which returns the value or a negative error code in one `int`, this
+    /// returns the value as `bool` and the error separately.
+    ///
+    /// See [gpiod_get_value](`https://docs.kernel.org/driver-api/gpio/index.html#c.gpiod_get_value`)
+    pub fn get_value(&self) -> Result<bool> {
+        // SAFETY: Type invariants insures that `self.0` is a valid and non-null pointer, hence it
+        // is safe to perform this FFI function call.
+        let ret = unsafe { bindings::gpiod_get_value(self.0.as_ptr()) };
+        to_result(ret)?;
+        Ok(ret != 0)
+    }
+
+    /// Read a GPIO's logical value from a context that may sleep.
+    ///
+    /// See [`Desc::get_value`] for the return value.
+    ///
+    /// See [gpiod_get_value_cansleep](`https://docs.kernel.org/driver-api/gpio/index.html#c.gpiod_get_value_cansleep`)
+    pub fn get_value_cansleep(&self) -> Result<bool> {
+        // SAFETY: Type invariants insures that `self.0` is a valid and non-null pointer, hence it
+        // is safe to perform this FFI function call.
+        let ret = unsafe { bindings::gpiod_get_value_cansleep(self.0.as_ptr()) };
+        to_result(ret)?;
+        Ok(ret != 0)
+    }
+
     /// Assign a GPIO's value.
     ///
     /// See [gpiod_set_value](`https://docs.kernel.org/driver-api/gpio/index.html#c.gpiod_set_value`)
@@ -82,3 +264,71 @@ fn drop(&mut self) {
 }
 
 unsafe impl Send for Desc {}
+
+/// An array of GPIOs obtained for one GPIO function, accessed together.
+///
+/// # Invariants
+///
+/// `self.0` points to a valid `struct gpio_descs` returned by `gpiod_get_array`, which is owned
+/// by this object.
+pub struct DescArray(NonNull<bindings::gpio_descs>);
+
+impl DescArray {
+    /// Obtain all GPIOs for a given GPIO function.
+    ///
+    /// See [gpiod_get_array](`https://docs.kernel.org/driver-api/gpio/index.html#c.gpiod_get_array`)
+    pub fn get(dev: &Device, con_id: &'static CStr, flags: Flags) -> Result<Self> {
+        // SAFETY: `dev.as_raw()` is a valid `struct device` and `con_id` a valid C string.
+        let descs = from_err_ptr(unsafe {
+            bindings::gpiod_get_array(dev.as_raw(), con_id.as_char_ptr(), flags as _)
+        })?;
+
+        Ok(Self(NonNull::new(descs).ok_or(EINVAL)?))
+    }
+
+    /// Returns the number of GPIOs in the array.
+    pub fn len(&self) -> usize {
+        // SAFETY: By the type invariant `self.0` points to a valid `struct gpio_descs`.
+        unsafe { (*self.0.as_ptr()).ndescs as usize }
+    }
+
+    /// Returns `true` if the array contains no GPIOs.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Assign the values of all GPIOs in the array from a context that may sleep.
+    ///
+    /// Bit `n` of `bitmap`, in the layout of kernel bitmaps, is the value of the `n`-th GPIO.
+    /// Returns [`EINVAL`] if `bitmap` has fewer bits than the array has GPIOs.
+    ///
+    /// See [gpiod_set_array_value_cansleep](`https://docs.kernel.org/driver-api/gpio/index.html#c.gpiod_set_array_value_cansleep`)
+    pub fn set_values(&mut self, bitmap: &[c_ulong]) -> Result {
+        let len = self.len();
+        if bitmap.len() * (c_ulong::BITS as usize) < len {
+            return Err(EINVAL);
+        }
+
+        let descs = self.0.as_ptr();
+        // SAFETY: By the type invariant `descs` points to a valid `struct gpio_descs` holding
+        // `len` descriptors. `bitmap` holds at least `len` bits and is only read.
+        to_result(unsafe {
+            bindings::gpiod_set_array_value_cansleep(
+                len as _,
+                (*descs).desc.as_mut_ptr(),
+                (*descs).info,
+                bitmap.as_ptr().cast_mut(),
+            )
+        })
+    }
+}
+
+impl Drop for DescArray {
+    fn drop(&mut self) {
+        // SAFETY: By the type invariant `self.0` is owned by this object and released only here.
+        unsafe { bindings::gpiod_put_array(self.0.as_ptr()) }
+    }
+}
+
+// SAFETY: The GPIO descriptors aren't tied to the thread that obtained them.
+unsafe impl Send for DescArray {}
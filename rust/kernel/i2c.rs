@@ -5,17 +5,19 @@
 //! C header: [`include/linux/i2c.h`](srctree/include/linux/i2c.h)
 
 use crate::{
+    arrayvec::ArrayVec,
     bindings, container_of,
     device::Device,
     device_id::{self, RawDeviceId},
     driver,
-    error::{to_result, Result},
+    error::{from_err_ptr, to_result, Result},
     of,
     prelude::*,
     str::CStr,
     types::{ARef, ForeignOwnable, Opaque},
     ThisModule,
 };
+use core::marker::PhantomData;
 
 /// Abstraction for `bindings::i2c_device_id`.
 #[repr(transparent)]
@@ -139,8 +141,18 @@ extern "C" fn remove_callback(client: *mut bindings::i2c_client) {
 
         // SAFETY: `remove_callback` is only ever called after a successful call to
         // `probe_callback`, hence it's guaranteed that `ptr` points to a valid and initialized
-        // `KBox<T>` pointer created through `KBox::into_foreign`.
-        let _ = unsafe { KBox::<T>::from_foreign(ptr) };
+        // `Pin<KBox<T>>` pointer created through `Pin::<KBox<T>>::into_foreign`.
+        let mut data = unsafe { Pin::<KBox<T>>::from_foreign(ptr) };
+
+        // SAFETY: The i2c bus only ever calls the remove callback with a valid `client`.
+        let dev = unsafe { Device::get_device(core::ptr::addr_of_mut!((*client).dev)) };
+        // SAFETY: `dev` is embedded in a valid `struct i2c_client`.
+        let mut client = unsafe { Client::from_dev(dev) };
+        data.as_mut().remove(&mut client);
+
+        // The driver data is dropped right here. Devres actions of the device only run after the
+        // remove callback returned, so they are still in place while the driver data is dropped.
+        drop(data);
     }
 }
 
@@ -203,7 +215,21 @@ pub trait Driver {
     /// I2C driver probe.
     ///
     /// Called when a new I2C client is added or discovered.
+    ///
+    /// The returned driver data is dropped when the client is unbound from the driver. This
+    /// happens before the devres actions registered for the client's device are run, so resources
+    /// owned by the driver data may still rely on device managed resources while being released.
+    /// The fields of the driver data are dropped in declaration order.
     fn probe(client: &mut Client, id_info: Option<&Self::IdInfo>) -> Result<Pin<KBox<Self>>>;
+
+    /// I2C driver remove.
+    ///
+    /// Called when the client is unbound from the driver, right before the driver data returned
+    /// by [`Driver::probe`] is dropped. Unlike `Drop`, this runs only for successfully probed
+    /// clients and has access to the client, so teardown that talks to the device belongs here.
+    ///
+    /// The default implementation does nothing.
+    fn remove(self: Pin<&mut Self>, _client: &mut Client) {}
 }
 
 /// An I2C Client.
@@ -233,6 +259,23 @@ pub fn as_raw(&self) -> *mut bindings::i2c_client {
         unsafe { container_of!(self.0.as_raw(), bindings::i2c_client, dev) }.cast_mut()
     }
 
+    /// Returns the address of the client on its adapter.
+    ///
+    /// The address is set by the I2C core when the client is created and doesn't change.
+    pub fn addr(&self) -> u16 {
+        // SAFETY: By the type invariant `self.as_raw` is a valid `struct i2c_client`.
+        unsafe { (*self.as_raw()).addr }
+    }
+
+    /// Returns the bus number of the adapter the client is on.
+    ///
+    /// The adapter is set by the I2C core when the client is created and doesn't change.
+    pub fn adapter_nr(&self) -> i32 {
+        // SAFETY: By the type invariant `self.as_raw` is a valid `struct i2c_client`, and its
+        // adapter is valid as long as `self` is registered on it.
+        unsafe { (*(*self.as_raw()).adapter).nr }
+    }
+
     pub fn new_client_device(&self, addr: u16) -> Option<Client> {
         let adapter = unsafe { *self.as_raw() }.adapter;
         // TODO: C driver used allocated the memory for the board info with
@@ -246,6 +289,293 @@ pub fn new_client_device(&self, addr: u16) -> Option<Client> {
         }
         Some(unsafe { Client::from_dev(Device::get_device(&mut (*client).dev)) })
     }
+
+    /// Create a dummy client at `addr` on the same adapter as `self`.
+    ///
+    /// Dummy clients are not bound to any driver, they only reserve the address and allow
+    /// accessing it, e.g. through a [`Regmap`](crate::regmap::Regmap).
+    ///
+    /// Returns [`EBUSY`] if `addr` is already used by another client on the adapter.
+    pub fn new_dummy(&self, addr: u16) -> Result<DummyClient> {
+        // SAFETY: By the type invariant `self.as_raw` is a valid `struct i2c_client`.
+        let adapter = unsafe { (*self.as_raw()).adapter };
+
+        // SAFETY: `adapter` is valid as long as `self` is registered on it. On address
+        // conflicts, `i2c_new_dummy_device` returns `ERR_PTR(-EBUSY)`.
+        let client = from_err_ptr(unsafe { bindings::i2c_new_dummy_device(adapter, addr) })?;
+
+        // SAFETY: `client` was just created by `i2c_new_dummy_device`.
+        Ok(unsafe { DummyClient::from_raw(client) })
+    }
+
+    /// Execute the I2C messages `msgs` as one combined transaction on the adapter of the client.
+    ///
+    /// Returns the number of messages that were executed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kernel::i2c::{Client, Msg};
+    ///
+    /// // Read register `reg` without a stop condition between the address and the data.
+    /// fn read_reg(client: &Client, reg: u8) -> Result<u8> {
+    ///     let mut val = [0u8];
+    ///     let mut msgs = [
+    ///         Msg::write(client.addr(), &[reg])?,
+    ///         Msg::read(client.addr(), &mut val)?,
+    ///     ];
+    ///     client.transfer(&mut msgs)?;
+    ///     Ok(val[0])
+    /// }
+    /// ```
+    pub fn transfer(&self, msgs: &mut [Msg<'_>]) -> Result<usize> {
+        let num = i32::try_from(msgs.len()).map_err(|_| EINVAL)?;
+
+        // SAFETY: By the type invariant `self.as_raw` is a valid `struct i2c_client`.
+        let adapter = unsafe { (*self.as_raw()).adapter };
+
+        // SAFETY: `adapter` is valid as long as `self` is registered on it. `Msg` is a
+        // transparent wrapper of `struct i2c_msg` and by its type invariants each message
+        // describes a buffer that is valid for the access implied by its flags.
+        let ret = unsafe { bindings::i2c_transfer(adapter, msgs.as_mut_ptr().cast(), num) };
+        to_result(ret)?;
+        Ok(ret as usize)
+    }
+
+    /// Set the timeout of transfers on the adapter of the client, in jiffies.
+    ///
+    /// This affects all clients on the adapter, not just this one. Values that don't fit the
+    /// adapter's `int` field are saturated.
+    pub fn set_timeout(&self, jiffies: u64) {
+        // SAFETY: By the type invariant `self.as_raw` is a valid `struct i2c_client`, and its
+        // adapter is valid as long as `self` is registered on it.
+        unsafe { set_adapter_timeout((*self.as_raw()).adapter, jiffies) }
+    }
+
+    /// Set how often transfers on the adapter of the client are retried on arbitration loss.
+    ///
+    /// This affects all clients on the adapter, not just this one. Values that don't fit the
+    /// adapter's `int` field are saturated.
+    pub fn set_retries(&self, n: u32) {
+        // SAFETY: By the type invariant `self.as_raw` is a valid `struct i2c_client`, and its
+        // adapter is valid as long as `self` is registered on it.
+        unsafe { set_adapter_retries((*self.as_raw()).adapter, n) }
+    }
+
+    /// Check that the adapter of the client supports all of the `I2C_FUNC_*` bits in `func`.
+    ///
+    /// Returns [`ENOTSUPP`] otherwise.
+    fn check_functionality(&self, func: u32) -> Result {
+        // SAFETY: By the type invariant `self.as_raw` is a valid `struct i2c_client`.
+        let adapter = unsafe { (*self.as_raw()).adapter };
+
+        // SAFETY: `adapter` is valid as long as `self` is registered on it, and a registered
+        // adapter always has an algorithm. This is what `i2c_get_functionality` does.
+        let functionality = unsafe { (*(*adapter).algo).functionality }.ok_or(ENOTSUPP)?;
+        // SAFETY: `adapter` is valid, see above.
+        if unsafe { functionality(adapter) } & func != func {
+            return Err(ENOTSUPP);
+        }
+        Ok(())
+    }
+
+    /// Read a byte from command register `cmd` with an SMBus read byte data transaction.
+    ///
+    /// Returns [`ENOTSUPP`] if the adapter doesn't support the transaction.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kernel::i2c::Client;
+    ///
+    /// fn set_bits(client: &Client, cmd: u8, bits: u8) -> Result {
+    ///     let val = client.smbus_read_byte_data(cmd)?;
+    ///     client.smbus_write_byte_data(cmd, val | bits)
+    /// }
+    /// ```
+    pub fn smbus_read_byte_data(&self, cmd: u8) -> Result<u8> {
+        self.check_functionality(bindings::I2C_FUNC_SMBUS_READ_BYTE_DATA)?;
+
+        // SAFETY: By the type invariant `self.as_raw` is a valid `struct i2c_client`.
+        let ret = unsafe { bindings::i2c_smbus_read_byte_data(self.as_raw(), cmd) };
+        to_result(ret)?;
+        Ok(ret as u8)
+    }
+
+    /// Write `value` to command register `cmd` with an SMBus write byte data transaction.
+    ///
+    /// Returns [`ENOTSUPP`] if the adapter doesn't support the transaction.
+    pub fn smbus_write_byte_data(&self, cmd: u8, value: u8) -> Result {
+        self.check_functionality(bindings::I2C_FUNC_SMBUS_WRITE_BYTE_DATA)?;
+
+        // SAFETY: By the type invariant `self.as_raw` is a valid `struct i2c_client`.
+        to_result(unsafe { bindings::i2c_smbus_write_byte_data(self.as_raw(), cmd, value) })
+    }
+
+    /// Read a 16-bit word from command register `cmd` with an SMBus read word data transaction.
+    ///
+    /// SMBus transfers the low byte first. Returns [`ENOTSUPP`] if the adapter doesn't support the
+    /// transaction.
+    pub fn smbus_read_word_data(&self, cmd: u8) -> Result<u16> {
+        self.check_functionality(bindings::I2C_FUNC_SMBUS_READ_WORD_DATA)?;
+
+        // SAFETY: By the type invariant `self.as_raw` is a valid `struct i2c_client`.
+        let ret = unsafe { bindings::i2c_smbus_read_word_data(self.as_raw(), cmd) };
+        to_result(ret)?;
+        Ok(ret as u16)
+    }
+
+    /// Write `value` to command register `cmd` with an SMBus write word data transaction.
+    ///
+    /// SMBus transfers the low byte first. Returns [`ENOTSUPP`] if the adapter doesn't support the
+    /// transaction.
+    pub fn smbus_write_word_data(&self, cmd: u8, value: u16) -> Result {
+        self.check_functionality(bindings::I2C_FUNC_SMBUS_WRITE_WORD_DATA)?;
+
+        // SAFETY: By the type invariant `self.as_raw` is a valid `struct i2c_client`.
+        to_result(unsafe { bindings::i2c_smbus_write_word_data(self.as_raw(), cmd, value) })
+    }
+
+    /// Read a block of up to 32 bytes from command register `cmd` with an SMBus block read.
+    ///
+    /// The device determines the number of bytes returned. Returns [`ENOTSUPP`] if the adapter
+    /// doesn't support the transaction.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kernel::i2c::Client;
+    ///
+    /// fn loopback(client: &Client) -> Result {
+    ///     client.smbus_write_block_data(0x10, b"ds90ub")?;
+    ///     let data = client.smbus_read_block_data(0x10)?;
+    ///     assert_eq!(data.as_ref(), b"ds90ub");
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn smbus_read_block_data(
+        &self,
+        cmd: u8,
+    ) -> Result<ArrayVec<{ bindings::I2C_SMBUS_BLOCK_MAX as usize }, u8>> {
+        self.check_functionality(bindings::I2C_FUNC_SMBUS_READ_BLOCK_DATA)?;
+
+        let mut data = ArrayVec::default();
+
+        // SAFETY: By the type invariant `self.as_raw` is a valid `struct i2c_client`. `data` has
+        // room for `I2C_SMBUS_BLOCK_MAX` bytes, which is the most `i2c_smbus_read_block_data`
+        // writes.
+        let ret =
+            unsafe { bindings::i2c_smbus_read_block_data(self.as_raw(), cmd, data.as_mut_ptr()) };
+        to_result(ret)?;
+
+        // SAFETY: On success, `ret` is the number of bytes written to `data`, which is at most
+        // `I2C_SMBUS_BLOCK_MAX`.
+        unsafe { data.set_len(ret as usize) };
+        Ok(data)
+    }
+
+    /// Write a block of up to 32 bytes to command register `cmd` with an SMBus block write.
+    ///
+    /// Returns [`EINVAL`] if `data` is longer than 32 bytes and [`ENOTSUPP`] if the adapter doesn't
+    /// support the transaction.
+    pub fn smbus_write_block_data(&self, cmd: u8, data: &[u8]) -> Result {
+        if data.len() > bindings::I2C_SMBUS_BLOCK_MAX as usize {
+            return Err(EINVAL);
+        }
+        self.check_functionality(bindings::I2C_FUNC_SMBUS_WRITE_BLOCK_DATA)?;
+
+        // SAFETY: By the type invariant `self.as_raw` is a valid `struct i2c_client`. `data` is
+        // valid for reads of `data.len()` bytes.
+        to_result(unsafe {
+            bindings::i2c_smbus_write_block_data(
+                self.as_raw(),
+                cmd,
+                data.len() as u8,
+                data.as_ptr(),
+            )
+        })
+    }
+}
+
+/// Set the `timeout` of `adapter`, saturating at `c_int::MAX`.
+///
+/// Like the C code setting it, e.g. the `I2C_TIMEOUT` ioctl, this doesn't lock the adapter.
+///
+/// # Safety
+///
+/// `adapter` must point to a valid `struct i2c_adapter`.
+unsafe fn set_adapter_timeout(adapter: *mut bindings::i2c_adapter, jiffies: u64) {
+    // SAFETY: The caller guarantees that `adapter` is valid.
+    unsafe { (*adapter).timeout = jiffies.try_into().unwrap_or(crate::ffi::c_int::MAX) };
+}
+
+/// Set the `retries` of `adapter`, saturating at `c_int::MAX`.
+///
+/// # Safety
+///
+/// `adapter` must point to a valid `struct i2c_adapter`.
+unsafe fn set_adapter_retries(adapter: *mut bindings::i2c_adapter, n: u32) {
+    // SAFETY: The caller guarantees that `adapter` is valid.
+    unsafe { (*adapter).retries = n.try_into().unwrap_or(crate::ffi::c_int::MAX) };
+}
+
+/// A message of an I2C transfer, see [`Client::transfer`].
+///
+/// # Invariants
+///
+/// `self.0.buf` points to `self.0.len` bytes that are valid for reads during `'a`, and also for
+/// writes if `I2C_M_RD` is set in `self.0.flags`. `I2C_M_RECV_LEN` and `I2C_M_REV_DIR_ADDR`,
+/// which change the length or the direction of the access, are never set.
+#[repr(transparent)]
+pub struct Msg<'a>(bindings::i2c_msg, PhantomData<&'a mut [u8]>);
+
+impl<'a> Msg<'a> {
+    /// Flags that may be passed to [`Msg::with_flags`].
+    pub const ALLOWED_FLAGS: u16 = (bindings::I2C_M_TEN
+        | bindings::I2C_M_NOSTART
+        | bindings::I2C_M_IGNORE_NAK
+        | bindings::I2C_M_NO_RD_ACK
+        | bindings::I2C_M_STOP) as u16;
+
+    fn new(addr: u16, flags: u16, buf: *mut u8, len: usize) -> Result<Self> {
+        let len = u16::try_from(len).map_err(|_| EINVAL)?;
+        Ok(Self(
+            bindings::i2c_msg {
+                addr,
+                flags,
+                len,
+                buf,
+            },
+            PhantomData,
+        ))
+    }
+
+    /// Create a message writing `buf` to the device at `addr`.
+    ///
+    /// Returns [`EINVAL`] if `buf` is longer than 65535 bytes.
+    pub fn write(addr: u16, buf: &'a [u8]) -> Result<Self> {
+        // INVARIANT: `buf` is valid for reads during `'a`, and `I2C_M_RD` is not set.
+        Self::new(addr, 0, buf.as_ptr().cast_mut(), buf.len())
+    }
+
+    /// Create a message reading `buf.len()` bytes from the device at `addr` into `buf`.
+    ///
+    /// Returns [`EINVAL`] if `buf` is longer than 65535 bytes.
+    pub fn read(addr: u16, buf: &'a mut [u8]) -> Result<Self> {
+        // INVARIANT: `buf` is valid for reads and writes during `'a`.
+        Self::new(addr, bindings::I2C_M_RD as u16, buf.as_mut_ptr(), buf.len())
+    }
+
+    /// Add protocol flags such as `I2C_M_TEN` or `I2C_M_NOSTART` to the message.
+    ///
+    /// Flags outside of [`Msg::ALLOWED_FLAGS`] are ignored, as they could make the adapter access
+    /// the buffer in a way it wasn't borrowed for.
+    pub fn with_flags(mut self, flags: u16) -> Self {
+        // INVARIANT: Only flags that neither change the direction nor the length of the access are
+        // added.
+        self.0.flags |= flags & Self::ALLOWED_FLAGS;
+        self
+    }
 }
 
 impl AsRef<Device> for Client {
@@ -254,6 +584,123 @@ fn as_ref(&self) -> &Device {
     }
 }
 
+/// A dummy I2C client created with [`Client::new_dummy`] or [`I2cAdapter::new_dummy`].
+///
+/// The client is unregistered when the `DummyClient` is dropped, releasing its address on the
+/// adapter. It can be used as a [`Client`] through [`Deref`](core::ops::Deref).
+///
+/// # Invariants
+///
+/// `self.0` was created by `i2c_new_dummy_device` and is only unregistered on drop.
+pub struct DummyClient(Client);
+
+impl DummyClient {
+    /// # Safety
+    ///
+    /// `client` must be a valid client returned by `i2c_new_dummy_device`, whose ownership is
+    /// transferred to the returned object.
+    unsafe fn from_raw(client: *mut bindings::i2c_client) -> Self {
+        // SAFETY: `client` is valid, so is the embedded `struct device`.
+        let dev = unsafe { Device::get_device(&mut (*client).dev) };
+        // SAFETY: `dev` is embedded in `client`.
+        // INVARIANT: The caller transfers ownership of the newly created dummy client.
+        Self(unsafe { Client::from_dev(dev) })
+    }
+}
+
+impl core::ops::Deref for DummyClient {
+    type Target = Client;
+
+    fn deref(&self) -> &Client {
+        &self.0
+    }
+}
+
+impl Drop for DummyClient {
+    fn drop(&mut self) {
+        // SAFETY: By the type invariant the client was created by `i2c_new_dummy_device` and is
+        // still registered. The reference held by `self.0` keeps the device alive until it is
+        // dropped right after.
+        unsafe { bindings::i2c_unregister_device(self.0.as_raw()) };
+    }
+}
+
+/// An I2C adapter, i.e. a bus segment that clients can be instantiated on.
+///
+/// Not to be confused with [`Adapter`], which registers I2C drivers.
+///
+/// # Invariants
+///
+/// `self.0` is a valid `struct i2c_adapter` on which a reference is held, taken with
+/// `i2c_get_adapter()` and released with `i2c_put_adapter()` on drop. The reference also pins the
+/// module owning the adapter, so an `I2cAdapter` should not be held longer than needed.
+pub struct I2cAdapter(core::ptr::NonNull<bindings::i2c_adapter>);
+
+impl I2cAdapter {
+    /// Get the adapter of the I2C bus with number `nr`, if it exists.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kernel::i2c::I2cAdapter;
+    ///
+    /// fn bus_present(nr: i32) -> bool {
+    ///     match I2cAdapter::get_by_nr(nr) {
+    ///         Some(adapter) => {
+    ///             assert_eq!(adapter.nr(), nr);
+    ///             true
+    ///         }
+    ///         None => false,
+    ///     }
+    /// }
+    /// ```
+    pub fn get_by_nr(nr: i32) -> Option<Self> {
+        // SAFETY: `i2c_get_adapter` is safe to call with any bus number, it returns null if there
+        // is no such adapter.
+        let adapter = unsafe { bindings::i2c_get_adapter(nr) };
+
+        // INVARIANT: `i2c_get_adapter` took a reference on the returned adapter.
+        core::ptr::NonNull::new(adapter).map(Self)
+    }
+
+    /// Returns the raw `struct i2c_adapter`.
+    pub fn as_raw(&self) -> *mut bindings::i2c_adapter {
+        self.0.as_ptr()
+    }
+
+    /// Returns the bus number of the adapter.
+    pub fn nr(&self) -> i32 {
+        // SAFETY: By the type invariant `self.as_raw` is a valid `struct i2c_adapter`.
+        unsafe { (*self.as_raw()).nr }
+    }
+
+    /// Create a dummy client at `addr` on this adapter.
+    ///
+    /// See [`Client::new_dummy`].
+    pub fn new_dummy(&self, addr: u16) -> Result<DummyClient> {
+        // SAFETY: By the type invariant `self.as_raw` is a valid `struct i2c_adapter`. On address
+        // conflicts, `i2c_new_dummy_device` returns `ERR_PTR(-EBUSY)`.
+        let client = from_err_ptr(unsafe { bindings::i2c_new_dummy_device(self.as_raw(), addr) })?;
+
+        // SAFETY: `client` was just created by `i2c_new_dummy_device`.
+        Ok(unsafe { DummyClient::from_raw(client) })
+    }
+}
+
+impl Drop for I2cAdapter {
+    fn drop(&mut self) {
+        // SAFETY: By the type invariant we hold a reference on `self.as_raw`.
+        unsafe { bindings::i2c_put_adapter(self.as_raw()) };
+    }
+}
+
+// SAFETY: The reference held on the adapter can be released from any thread.
+unsafe impl Send for I2cAdapter {}
+
+// SAFETY: `I2cAdapter` only exposes the immutable bus number and operations that are protected by
+// the synchronization of the I2C core.
+unsafe impl Sync for I2cAdapter {}
+
 /// Declares a kernel module that exposes a single I2C driver.
 ///
 /// # Examples
@@ -300,3 +747,30 @@ macro_rules! i2c_device_table {
         $crate::module_device_table!("i2c", $module_table_name, $table_name);
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_adapter_timeout_and_retries() {
+        // SAFETY: `struct i2c_adapter` is valid when zero-initialized.
+        let mut adapter: bindings::i2c_adapter = unsafe { core::mem::zeroed() };
+
+        // SAFETY: `adapter` is valid.
+        unsafe {
+            set_adapter_timeout(&mut adapter, 250);
+            set_adapter_retries(&mut adapter, 3);
+        }
+        assert_eq!(adapter.timeout, 250);
+        assert_eq!(adapter.retries, 3);
+
+        // SAFETY: `adapter` is valid.
+        unsafe {
+            set_adapter_timeout(&mut adapter, u64::MAX);
+            set_adapter_retries(&mut adapter, u32::MAX);
+        }
+        assert_eq!(adapter.timeout, crate::ffi::c_int::MAX);
+        assert_eq!(adapter.retries, crate::ffi::c_int::MAX);
+    }
+}
@@ -79,6 +79,8 @@
 pub mod security;
 pub mod seq_file;
 pub mod sizes;
+#[cfg(CONFIG_SPI)]
+pub mod spi;
 mod static_assert;
 #[doc(hidden)]
 pub mod std_vendor;
@@ -45,6 +45,55 @@ pub const fn new(compatible: &'static CStr) -> Self {
     }
 }
 
+impl<U> dyn kernel::device_id::IdTable<DeviceId, U> {
+    /// Look up the driver-specific information for an OF 'compatible' string.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kernel::{c_str, device_id::IdArray, of};
+    ///
+    /// const TABLE: IdArray<of::DeviceId, u32, 2> = IdArray::new([
+    ///     (of::DeviceId::new(c_str!("ti,ds90ub953")), 953),
+    ///     (of::DeviceId::new(c_str!("ti,ds90ub913")), 913),
+    /// ]);
+    /// let table: of::IdTable<u32> = &TABLE;
+    ///
+    /// assert_eq!(table.find(c_str!("ti,ds90ub913")), Some(&913));
+    /// assert_eq!(table.find(c_str!("ti,ds90ub960")), None);
+    /// ```
+    pub fn find(&self, compatible: &CStr) -> Option<&U> {
+        let mut ptr = self.as_ptr();
+        let mut index = 0;
+        loop {
+            // SAFETY: `IdTable::as_ptr` points to a zero-terminated array and we stop iterating
+            // when reaching the sentinel.
+            let id = unsafe { &*ptr };
+            if id.name[0] == 0 && id.type_[0] == 0 && id.compatible[0] == 0 {
+                return None;
+            }
+
+            let len = id
+                .compatible
+                .iter()
+                .position(|&c| c == 0)
+                .unwrap_or(id.compatible.len());
+            let matches = id.compatible[..len]
+                .iter()
+                .map(|&c| c as u8)
+                .eq(compatible.as_bytes().iter().copied());
+            if matches {
+                return Some(self.info(index));
+            }
+
+            // SAFETY: The current entry is not the sentinel, hence there is at least one more
+            // entry in the array.
+            ptr = unsafe { ptr.add(1) };
+            index += 1;
+        }
+    }
+}
+
 /// Create an OF `IdTable` with an "alias" for modpost.
 #[macro_export]
 macro_rules! of_device_table {
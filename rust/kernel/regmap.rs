@@ -22,24 +22,38 @@
 //! });
 //!
 //! fn probe(client: &mut i2c::Client) -> Result {
-//!     let config = regmap::Config::<AccessOps>::new(8, 8)
+//!     let config = regmap::Config::new(8, 8)
+//!         .with_access_ops::<AccessOps>()
 //!         .with_max_register(0x16)
 //!         .with_cache_type(regmap::CacheType::RbTree);
-//!     let regmap = regmap::Regmap::init_i2c(client, &config);
-//!     let mut fields = regmap.alloc_fields(&FIELD_DESCS)?;
+//!     let regmap = regmap::Regmap::init_i2c_arc(client, &config)?;
+//!     let mut fields = regmap::Fields::new(&regmap, &FIELD_DESCS)?;
 //!
 //!     dev_info!(client.as_ref(), "PID: {:#x}", pid::value::read(&mut fields)?);
 //! }
 //! ```
 
+#[cfg(any(
+    CONFIG_REGMAP_I2C = "y",
+    CONFIG_REGMAP_SPI = "y",
+    CONFIG_REGMAP_MMIO = "y"
+))]
+use crate::error::from_err_ptr;
+#[cfg(CONFIG_REGMAP_I2C = "y")]
+use crate::i2c;
+#[cfg(CONFIG_REGMAP_SPI = "y")]
+use crate::spi;
+#[cfg(CONFIG_REGMAP_MMIO = "y")]
+use crate::str::CStr;
 use crate::{
-    bindings,
+    alloc::{flags::GFP_KERNEL, KVec},
+    bindings, c_str, delay,
+    device::Device,
     error::{code::*, to_result, Error, Result},
+    fwnode::FwNode,
     macros::paste,
     sync::Arc,
 };
-#[cfg(CONFIG_REGMAP_I2C = "y")]
-use crate::{error::from_err_ptr, i2c};
 use core::ptr::NonNull;
 
 /// Type of caching
@@ -64,27 +78,167 @@ pub enum CacheType {
 ///
 /// # Invariants
 ///
-/// * `self.0` is valid, non-zero, and the memory is owned by `self`.
+/// * `self.ptr` is valid, non-zero, and the memory is owned by `self`.
 /// * This abstraction does not allow to disable regmap locking.
-pub struct Regmap(NonNull<bindings::regmap>);
+pub struct Regmap {
+    ptr: NonNull<bindings::regmap>,
+    val_format: ValFormat,
+}
 
 impl Regmap {
     #[cfg(CONFIG_REGMAP_I2C = "y")]
     /// Initialize a [`Regmap`] instance for an `i2c` client.
     pub fn init_i2c(i2c: &i2c::Client, config: &Config) -> Result<Self> {
-        // SAFETY: Type invariants guarantee that `i2c.as_raw` is valid and non-null and
-        // the Config type invariant guarantee that `config.raw` always contains valid data.
-        let regmap = from_err_ptr(unsafe { bindings::regmap_init_i2c(i2c.as_raw(), &config.raw) })?;
+        // SAFETY: `i2c.as_ref().as_raw()` is valid and non-null.
+        let regmap = unsafe {
+            config.init_with_volatile_fn(i2c.as_ref().as_raw(), || {
+                // SAFETY: Type invariants guarantee that `i2c.as_raw` is valid and non-null and
+                // the Config type invariant guarantee that `config.raw` always contains valid
+                // data.
+                from_err_ptr(unsafe { bindings::regmap_init_i2c(i2c.as_raw(), &config.raw) })
+            })
+        }?;
+
+        Ok(Regmap {
+            ptr: NonNull::new(regmap).ok_or(EINVAL)?,
+            val_format: ValFormat::from_config(i2c.as_ref(), config, Endian::Big),
+        })
+    }
+
+    #[cfg(CONFIG_REGMAP_I2C = "y")]
+    /// Initialize a reference-counted [`Regmap`] instance for an `i2c` client.
+    ///
+    /// This is a convenience wrapper around [`Regmap::init_i2c`] for users that need to share
+    /// the regmap, e.g. with [`Fields::new`].
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// fn probe(client: &mut i2c::Client) -> Result {
+    ///     let config = regmap::Config::new(8, 8).with_access_ops::<AccessOps>();
+    ///     let regmap = regmap::Regmap::init_i2c_arc(client, &config)?;
+    ///     let fields = regmap::Fields::new(&regmap, &FIELD_DESCS)?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn init_i2c_arc(i2c: &i2c::Client, config: &Config) -> Result<Arc<Self>> {
+        Ok(Arc::new(Self::init_i2c(i2c, config)?, GFP_KERNEL)?)
+    }
+
+    #[cfg(CONFIG_REGMAP_SPI = "y")]
+    /// Initialize a [`Regmap`] instance for an SPI device.
+    pub fn init_spi(spi: &spi::Device, config: &Config) -> Result<Self> {
+        // SAFETY: `spi.as_ref().as_raw()` is valid and non-null.
+        let regmap = unsafe {
+            config.init_with_volatile_fn(spi.as_ref().as_raw(), || {
+                // SAFETY: Type invariants guarantee that `spi.as_raw` is valid and non-null and
+                // the Config type invariant guarantee that `config.raw` always contains valid
+                // data.
+                from_err_ptr(unsafe { bindings::regmap_init_spi(spi.as_raw(), &config.raw) })
+            })
+        }?;
+
+        Ok(Regmap {
+            ptr: NonNull::new(regmap).ok_or(EINVAL)?,
+            val_format: ValFormat::from_config(spi.as_ref(), config, Endian::Big),
+        })
+    }
+
+    #[cfg(CONFIG_REGMAP_SPI = "y")]
+    /// Initialize a reference-counted [`Regmap`] instance for an SPI device.
+    ///
+    /// See [`Regmap::init_i2c_arc`].
+    pub fn init_spi_arc(spi: &spi::Device, config: &Config) -> Result<Arc<Self>> {
+        Ok(Arc::new(Self::init_spi(spi, config)?, GFP_KERNEL)?)
+    }
+
+    #[cfg(CONFIG_REGMAP_MMIO = "y")]
+    /// Initialize a [`Regmap`] instance for memory mapped registers at `base`.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// fn probe(pdev: &platform::Device, iomem: &Io<0x100>) -> Result {
+    ///     let config = regmap::Config::new(32, 32).with_max_register(0xfc);
+    ///     // SAFETY: `iomem` is only unmapped after the regmap is dropped in `remove`.
+    ///     let regmap =
+    ///         unsafe { regmap::Regmap::init_mmio(pdev.as_ref(), iomem.addr() as _, &config)? };
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// # Safety
+    ///
+    /// `base` must point to an I/O memory mapping covering all registers up to the configured
+    /// maximum register, which stays mapped for the whole lifetime of the returned [`Regmap`].
+    pub unsafe fn init_mmio(
+        dev: &Device,
+        base: *mut kernel::ffi::c_void,
+        config: &Config,
+    ) -> Result<Self> {
+        // SAFETY: By the safety requirements of this function, `base` is valid.
+        unsafe { Self::init_mmio_clk(dev, None, base, config) }
+    }
+
+    #[cfg(CONFIG_REGMAP_MMIO = "y")]
+    /// Initialize a [`Regmap`] instance for memory mapped registers at `base`, which need the
+    /// clock `clk_id` of `dev` to be enabled while accessing them.
+    ///
+    /// # Safety
+    ///
+    /// See [`Regmap::init_mmio`].
+    pub unsafe fn init_mmio_clk(
+        dev: &Device,
+        clk_id: Option<&CStr>,
+        base: *mut kernel::ffi::c_void,
+        config: &Config,
+    ) -> Result<Self> {
+        let clk_id = clk_id.map_or(core::ptr::null(), CStr::as_char_ptr);
+        // SAFETY: `dev.as_raw()` is valid and non-null.
+        let regmap = unsafe {
+            config.init_with_volatile_fn(dev.as_raw(), || {
+                // SAFETY: `dev.as_raw` is valid and non-null, `clk_id` is either null or a valid
+                // null-terminated string, `base` is valid by the safety requirements of this
+                // function and the Config type invariant guarantee that `config.raw` always
+                // contains valid data.
+                from_err_ptr(unsafe {
+                    bindings::regmap_init_mmio_clk(dev.as_raw(), clk_id, base.cast(), &config.raw)
+                })
+            })
+        }?;
 
-        Ok(Regmap(NonNull::new(regmap).ok_or(EINVAL)?))
+        Ok(Regmap {
+            ptr: NonNull::new(regmap).ok_or(EINVAL)?,
+            val_format: ValFormat::from_config(dev, config, Endian::Little),
+        })
     }
 
     /// Return the raw pointer of this regmap.
     pub fn as_raw(&self) -> *mut bindings::regmap {
-        self.0.as_ptr()
+        self.ptr.as_ptr()
     }
 
-    pub fn read(&mut self, register: u32) -> Result<u32> {
+    /// Return the format of register values on the bus, as configured at initialization.
+    pub fn val_format(&self) -> ValFormat {
+        self.val_format
+    }
+
+    /// Decode a register value from its raw bus representation in `buf`.
+    ///
+    /// See [`ValFormat::parse`].
+    pub fn parse_val(&self, buf: &[u8]) -> u32 {
+        self.val_format.parse(buf)
+    }
+
+    /// Encode `val` into its raw bus representation in `buf`.
+    ///
+    /// See [`ValFormat::format`].
+    pub fn format_val(&self, val: u32, buf: &mut [u8]) {
+        self.val_format.format(val, buf)
+    }
+
+    /// Read the value of `register`.
+    pub fn read(&self, register: u32) -> Result<u32> {
         let mut value = 0;
         // SAFETY: By the type invariant, `self.as_raw` is a valid pointer.
         let ret = unsafe { bindings::regmap_read(self.as_raw(), register, &mut value) };
@@ -92,9 +246,191 @@ pub fn read(&mut self, register: u32) -> Result<u32> {
         Ok(value)
     }
 
+    /// Write `value` to `register`.
     pub fn write(&self, register: u32, value: u32) -> Result<()> {
+        // SAFETY: By the type invariant, `self.as_raw` is a valid pointer.
         to_result(unsafe { bindings::regmap_write(self.as_raw(), register, value) })
     }
+
+    /// Update the bits of `register` selected by `mask` to `value`, under the regmap lock.
+    ///
+    /// The register is only written if its value changes.
+    pub fn update_bits(&self, register: u32, mask: u32, value: u32) -> Result {
+        // SAFETY: By the type invariant, `self.as_raw` is a valid pointer. `change` may be null.
+        to_result(unsafe {
+            bindings::regmap_update_bits_base(
+                self.as_raw(),
+                register,
+                mask,
+                value,
+                core::ptr::null_mut(),
+                false,
+                false,
+            )
+        })
+    }
+
+    /// Like [`Regmap::update_bits`], but always writes the register, even if its value doesn't
+    /// change.
+    pub fn force_update_bits(&self, register: u32, mask: u32, value: u32) -> Result {
+        // SAFETY: By the type invariant, `self.as_raw` is a valid pointer. `change` may be null.
+        to_result(unsafe {
+            bindings::regmap_update_bits_base(
+                self.as_raw(),
+                register,
+                mask,
+                value,
+                core::ptr::null_mut(),
+                false,
+                true,
+            )
+        })
+    }
+
+    /// Read consecutive registers starting at `register` into `buf`.
+    ///
+    /// Each register takes [`ValFormat::bytes`] bytes of `buf`, stored in CPU byte order. The
+    /// length of `buf` must be a multiple of that, otherwise [`EINVAL`] is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// // Read the six ID registers of a device with 8-bit registers in one go.
+    /// let mut id = [0; 6];
+    /// regmap.bulk_read(0xf0, &mut id)?;
+    /// ```
+    pub fn bulk_read(&self, register: u32, buf: &mut [u8]) -> Result {
+        let count = self.bulk_count(buf.len())?;
+        // SAFETY: By the type invariant, `self.as_raw` is a valid pointer. `buf` is valid for
+        // writing `count` values of `self.val_format.bytes()` bytes each.
+        to_result(unsafe {
+            bindings::regmap_bulk_read(self.as_raw(), register, buf.as_mut_ptr().cast(), count)
+        })
+    }
+
+    /// Write `buf` to consecutive registers starting at `register`.
+    ///
+    /// The layout of `buf` is the same as for [`Regmap::bulk_read`].
+    pub fn bulk_write(&self, register: u32, buf: &[u8]) -> Result {
+        let count = self.bulk_count(buf.len())?;
+        // SAFETY: By the type invariant, `self.as_raw` is a valid pointer. `buf` is valid for
+        // reading `count` values of `self.val_format.bytes()` bytes each.
+        to_result(unsafe {
+            bindings::regmap_bulk_write(self.as_raw(), register, buf.as_ptr().cast(), count)
+        })
+    }
+
+    /// Write the `(register, value)` pairs of `regs` in order, under a single regmap lock.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// fn init(regmap: &Regmap) -> Result {
+    ///     regmap.multi_reg_write(&[(0x10, 0x01), (0x11, 0x80), (0x10, 0x03)])
+    /// }
+    /// ```
+    pub fn multi_reg_write(&self, regs: &[(u32, u32)]) -> Result {
+        let seq = reg_sequence(regs)?;
+        let num_regs = i32::try_from(seq.len())?;
+        // SAFETY: By the type invariant, `self.as_raw` is a valid pointer. `seq` is valid for
+        // reading `num_regs` entries.
+        to_result(unsafe {
+            bindings::regmap_multi_reg_write(self.as_raw(), seq.as_ptr(), num_regs)
+        })
+    }
+
+    /// Write all cached register values that differ from their defaults back to the hardware.
+    ///
+    /// Use this after the device lost its state, e.g. when resuming after its supply was
+    /// turned off.
+    pub fn cache_sync(&self) -> Result {
+        // SAFETY: By the type invariant, `self.as_raw` is a valid pointer.
+        to_result(unsafe { bindings::regcache_sync(self.as_raw()) })
+    }
+
+    /// Enable or disable cache-only mode.
+    ///
+    /// While enabled, writes only update the cache and reads of uncached registers fail. This
+    /// allows to keep accessing a device that is powered down, to later apply the changes with
+    /// [`Regmap::cache_sync`].
+    ///
+    /// Unlike the other cache functions this can't fail, so it doesn't return a [`Result`].
+    pub fn cache_only(&self, enable: bool) {
+        // SAFETY: By the type invariant, `self.as_raw` is a valid pointer and regmap locking is
+        // enabled, which serializes the update of the cache state.
+        unsafe { bindings::regcache_cache_only(self.as_raw(), enable) }
+    }
+
+    /// Discard the cached values of the registers from `min` to `max`, inclusive.
+    ///
+    /// The next read of these registers goes to the hardware.
+    pub fn cache_drop_region(&self, min: u32, max: u32) -> Result {
+        if min > max {
+            return Err(EINVAL);
+        }
+        // SAFETY: By the type invariant, `self.as_raw` is a valid pointer.
+        to_result(unsafe { bindings::regcache_drop_region(self.as_raw(), min, max) })
+    }
+
+    /// Wait until `bit` of `register` is `set`, for at most `timeout_us` microseconds.
+    ///
+    /// Returns [`EINVAL`] if `bit` doesn't fit a register value and [`ETIMEDOUT`] if the bit
+    /// didn't reach the requested state in time. Read errors are passed on immediately.
+    pub fn wait_for_bit(&self, register: u32, bit: u32, set: bool, timeout_us: u64) -> Result {
+        wait_for_bit_with(
+            &delay::KernelClock,
+            || self.read(register),
+            bit,
+            set,
+            timeout_us,
+        )
+    }
+
+    /// Number of registers covered by a bulk access buffer of `len` bytes.
+    fn bulk_count(&self, len: usize) -> Result<usize> {
+        let bytes = self.val_format.bytes();
+        if bytes == 0 || len % bytes != 0 {
+            return Err(EINVAL);
+        }
+        Ok(len / bytes)
+    }
+}
+
+/// Convert `(register, value)` pairs to the sequence taken by `regmap_multi_reg_write`.
+fn reg_sequence(regs: &[(u32, u32)]) -> Result<KVec<bindings::reg_sequence>> {
+    let mut seq = KVec::with_capacity(regs.len(), GFP_KERNEL)?;
+    for &(reg, def) in regs {
+        seq.push(
+            bindings::reg_sequence {
+                reg,
+                def,
+                delay_us: 0,
+            },
+            GFP_KERNEL,
+        )?;
+    }
+    Ok(seq)
+}
+
+/// Microseconds to sleep between two reads in [`Regmap::wait_for_bit`].
+const WAIT_FOR_BIT_SLEEP_US: u64 = 100;
+
+fn wait_for_bit_with<C: delay::Clock>(
+    clock: &C,
+    read: impl FnMut() -> Result<u32>,
+    bit: u32,
+    set: bool,
+    timeout_us: u64,
+) -> Result {
+    let mask = 1u32.checked_shl(bit).ok_or(EINVAL)?;
+    delay::read_poll_timeout_us_with(
+        clock,
+        read,
+        |&val| (val & mask != 0) == set,
+        WAIT_FOR_BIT_SLEEP_US,
+        timeout_us,
+    )
+    .map(|_| ())
 }
 
 impl Drop for Regmap {
@@ -147,8 +483,29 @@ pub const fn len(&self) -> usize {
 
 /// Regmap fields
 ///
+/// Any number of [`Fields`] can be allocated from the same [`Regmap`], e.g. to describe
+/// different register sets of a device. Each of them keeps a reference to the regmap, so the
+/// regmap is only freed once all of them have been dropped, in whichever order.
+///
+/// # Examples
+///
+/// ```ignore
+/// let regmap = regmap::Regmap::init_i2c_arc(client, &config)?;
+/// let mut status = regmap::Fields::new(&regmap, &STATUS_DESCS)?;
+/// let mut control = regmap::Fields::new(&regmap, &CONTROL_DESCS)?;
+/// drop(regmap);
+///
+/// status::lock::is_set(&mut status)?;
+/// control::enable::set(&mut control)?;
+///
+/// drop(status);
+/// // The regmap is still alive, `control` holds the last reference.
+/// control::enable::clear(&mut control)?;
+/// ```
+///
 /// # Invariants
 ///
+/// `N` is never zero.
 /// `self.fields` array is garanteed to contains valid and non-null pointers.
 /// `self.fields[0]` memory is owned by `Fields`.
 /// `self.fields[*]` values cannot be modified.
@@ -157,13 +514,18 @@ pub struct Fields<const N: usize> {
 
     // Each regmap_field hold a pointer to the `struct regmap` instance, so we need to keep a copy
     // of the wrapper around.
-    _regmap: Arc<Regmap>,
+    regmap: Arc<Regmap>,
 }
 impl<const N: usize> Fields<N> {
     /// Allocate regmap [`Fields`]
     ///
     /// This function allocate regmap fields from the `reg_fields` descriptors
     pub fn new(regmap: &Arc<Regmap>, descs: &'static FieldDescs<N>) -> Result<Self> {
+        // `Drop` frees the bulk allocation through the first field.
+        if N == 0 {
+            return Err(EINVAL);
+        }
+
         let mut fields = [NonNull::<bindings::regmap_field>::dangling(); N];
         // SAFETY:
         // * [`Regmap`] type invariants guarantee that `Regmap::as_raw` returns a valid pointer.
@@ -177,12 +539,32 @@ pub fn new(regmap: &Arc<Regmap>, descs: &'static FieldDescs<N>) -> Result<Self>
             )
         })?;
 
+        // INVARIANT: `N` is non-zero and `regmap_field_bulk_alloc` succeeded, so all entries of
+        // `fields` are valid.
         Ok(Fields {
             fields,
-            _regmap: regmap.clone(),
+            regmap: regmap.clone(),
         })
     }
 
+    /// Get the register map the fields belong to
+    ///
+    /// This allows accessing registers that are not covered by a field. Such accesses go through
+    /// the same regmap, and so the same lock and cache, as the field accesses.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// fn dump_pid(fields: &mut regmap::Fields<{ FIELD_DESCS.len() }>) -> Result {
+    ///     let raw = fields.regmap().read(0x3)?;
+    ///     assert_eq!(raw, pid::value::read(fields)?);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn regmap(&self) -> &Arc<Regmap> {
+        &self.regmap
+    }
+
     /// Get field `index`
     pub fn index(&mut self, index: usize) -> *mut bindings::regmap_field {
         self.fields[index].as_ptr()
@@ -207,6 +589,50 @@ pub fn read(&mut self, index: usize) -> Result<kernel::ffi::c_uint> {
 
         Ok(val)
     }
+
+    /// Read instance `id` of the indexed field `index`
+    ///
+    /// Indexed fields are declared with `[id_size; id_offset]`, see [`regmap_field_raw`]. Returns
+    /// [`EINVAL`] if `id` is not smaller than the field's `id_size`.
+    pub fn read_indexed(&mut self, index: usize, id: u32) -> Result<kernel::ffi::c_uint> {
+        let mut val = 0;
+
+        if index >= N {
+            return Err(EINVAL);
+        }
+
+        // SAFETY: By the type invariants, we are garanteed that all fields entries point
+        // to valid and initialized values, hence it is safe to make this FFI call.
+        to_result(unsafe {
+            bindings::regmap_fields_read(self.fields[index].as_ptr(), id, &mut val)
+        })?;
+
+        Ok(val)
+    }
+
+    /// Write `val` to instance `id` of the indexed field `index`
+    ///
+    /// See [`Fields::read_indexed`].
+    pub fn write_indexed(&mut self, index: usize, id: u32, val: kernel::ffi::c_uint) -> Result {
+        if index >= N {
+            return Err(EINVAL);
+        }
+
+        // SAFETY: By the type invariants, we are garanteed that all fields entries point
+        // to valid and initialized values, hence it is safe to make this FFI call. `change` may
+        // be null.
+        to_result(unsafe {
+            bindings::regmap_fields_update_bits_base(
+                self.fields[index].as_ptr(),
+                id,
+                !0,
+                val,
+                core::ptr::null_mut(),
+                false,
+                false,
+            )
+        })
+    }
 }
 
 impl<const N: usize> Drop for Fields<N> {
@@ -218,7 +644,7 @@ fn drop(&mut self) {
 }
 
 // SAFETY: The type invariants guarantee that we own the `struct regmap_field` data and that they
-// cannot be modified after allocation, and _regmap is Send, so it is safe for `Fields` to be Send.
+// cannot be modified after allocation, and regmap is Send, so it is safe for `Fields` to be Send.
 unsafe impl<const N: usize> Send for Fields<N> {}
 
 macro_rules! config_with {
@@ -246,13 +672,206 @@ pub trait ConfigOps {
     fn is_precious_reg(reg: u32) -> bool;
 }
 
+/// Byte order of register values on the bus.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u32)]
+pub enum Endian {
+    /// Use the byte order requested by a `big-endian`, `little-endian` or `native-endian`
+    /// firmware property of the device, or else the default of the bus. That is big endian for
+    /// I2C and SPI, and little endian for MMIO.
+    Default = bindings::regmap_endian_REGMAP_ENDIAN_DEFAULT,
+    /// Big endian.
+    Big = bindings::regmap_endian_REGMAP_ENDIAN_BIG,
+    /// Little endian.
+    Little = bindings::regmap_endian_REGMAP_ENDIAN_LITTLE,
+    /// Byte order of the CPU.
+    Native = bindings::regmap_endian_REGMAP_ENDIAN_NATIVE,
+}
+
+/// Size and byte order of register values on the bus.
+///
+/// # Examples
+///
+/// ```
+/// use kernel::regmap::{Endian, ValFormat};
+///
+/// let mut buf = [0u8; 4];
+///
+/// let fmt = ValFormat::new(8, Endian::Big);
+/// fmt.format(0xab, &mut buf);
+/// assert_eq!(buf[..1], [0xab]);
+/// assert_eq!(fmt.parse(&buf), 0xab);
+///
+/// let fmt = ValFormat::new(8, Endian::Little);
+/// fmt.format(0xcd, &mut buf);
+/// assert_eq!(buf[..1], [0xcd]);
+/// assert_eq!(fmt.parse(&buf), 0xcd);
+///
+/// let fmt = ValFormat::new(16, Endian::Big);
+/// fmt.format(0x1234, &mut buf);
+/// assert_eq!(buf[..2], [0x12, 0x34]);
+/// assert_eq!(fmt.parse(&buf), 0x1234);
+///
+/// let fmt = ValFormat::new(16, Endian::Little);
+/// fmt.format(0x1234, &mut buf);
+/// assert_eq!(buf[..2], [0x34, 0x12]);
+/// assert_eq!(fmt.parse(&buf), 0x1234);
+///
+/// let fmt = ValFormat::new(32, Endian::Big);
+/// fmt.format(0x1234_5678, &mut buf);
+/// assert_eq!(buf, [0x12, 0x34, 0x56, 0x78]);
+/// assert_eq!(fmt.parse(&buf), 0x1234_5678);
+///
+/// let fmt = ValFormat::new(32, Endian::Little);
+/// fmt.format(0x1234_5678, &mut buf);
+/// assert_eq!(buf, [0x78, 0x56, 0x34, 0x12]);
+/// assert_eq!(fmt.parse(&buf), 0x1234_5678);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ValFormat {
+    bytes: usize,
+    big_endian: bool,
+}
+
+impl ValFormat {
+    /// Create the format for values of `val_bits` bits in the given byte order.
+    ///
+    /// Values are padded to whole bytes, `val_bits` is clamped to the 32 bits of a `u32`.
+    /// [`Endian::Default`] depends on the device and bus, so it is taken as big endian here.
+    /// [`Regmap::val_format`] returns the byte order resolved when the regmap was created.
+    pub const fn new(val_bits: u32, endian: Endian) -> Self {
+        let val_bits = if val_bits > u32::BITS {
+            u32::BITS
+        } else {
+            val_bits
+        };
+        let big_endian = match endian {
+            Endian::Default | Endian::Big => true,
+            Endian::Little => false,
+            Endian::Native => cfg!(target_endian = "big"),
+        };
+
+        Self {
+            bytes: val_bits.div_ceil(8) as usize,
+            big_endian,
+        }
+    }
+
+    /// Format of the values of a regmap of `dev` with `config`, on a bus that uses
+    /// `bus_default` for [`Endian::Default`].
+    #[cfg_attr(
+        not(any(
+            CONFIG_REGMAP_I2C = "y",
+            CONFIG_REGMAP_SPI = "y",
+            CONFIG_REGMAP_MMIO = "y"
+        )),
+        allow(dead_code)
+    )]
+    fn from_config(dev: &Device, config: &Config, bus_default: Endian) -> Self {
+        let endian = match config.raw.val_format_endian {
+            bindings::regmap_endian_REGMAP_ENDIAN_BIG => Endian::Big,
+            bindings::regmap_endian_REGMAP_ENDIAN_LITTLE => Endian::Little,
+            bindings::regmap_endian_REGMAP_ENDIAN_NATIVE => Endian::Native,
+            _ => default_val_endian(dev, bus_default),
+        };
+
+        Self::new(config.raw.val_bits as u32, endian)
+    }
+
+    /// Number of bytes a value occupies on the bus.
+    pub const fn bytes(&self) -> usize {
+        self.bytes
+    }
+
+    /// Decode a value from the first [`ValFormat::bytes`] bytes of `buf`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `buf` is shorter than [`ValFormat::bytes`].
+    pub fn parse(&self, buf: &[u8]) -> u32 {
+        let buf = &buf[..self.bytes];
+        let fold = |val: u32, b: &u8| (val << 8) | u32::from(*b);
+        if self.big_endian {
+            buf.iter().fold(0, fold)
+        } else {
+            buf.iter().rev().fold(0, fold)
+        }
+    }
+
+    /// Encode `val` into the first [`ValFormat::bytes`] bytes of `buf`.
+    ///
+    /// Bits of `val` that don't fit are discarded.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `buf` is shorter than [`ValFormat::bytes`].
+    pub fn format(&self, val: u32, buf: &mut [u8]) {
+        let buf = &mut buf[..self.bytes];
+        for (i, b) in buf.iter_mut().enumerate() {
+            let shift = if self.big_endian {
+                8 * (self.bytes - 1 - i)
+            } else {
+                8 * i
+            };
+            *b = (val >> shift) as u8;
+        }
+    }
+}
+
+/// Byte order `regmap_get_val_endian()` uses for [`Endian::Default`] on a regmap of `dev`.
+///
+/// This is the byte order requested by a firmware property of `dev`, or else `bus_default`.
+#[cfg_attr(
+    not(any(
+        CONFIG_REGMAP_I2C = "y",
+        CONFIG_REGMAP_SPI = "y",
+        CONFIG_REGMAP_MMIO = "y"
+    )),
+    allow(dead_code)
+)]
+fn default_val_endian(dev: &Device, bus_default: Endian) -> Endian {
+    // SAFETY: `dev.as_raw()` is valid and non-null.
+    let fwnode = unsafe { bindings::dev_fwnode(dev.as_raw()) };
+    if fwnode.is_null() {
+        return bus_default;
+    }
+    // SAFETY: `fwnode` is valid and non-null, and stays valid at least as long as `dev`.
+    let fwnode = unsafe { &*fwnode.cast::<FwNode>() };
+
+    [
+        (c_str!("big-endian"), Endian::Big),
+        (c_str!("little-endian"), Endian::Little),
+        (c_str!("native-endian"), Endian::Native),
+    ]
+    .into_iter()
+    .find(|(name, _)| fwnode.property_read_bool(name))
+    .map_or(bus_default, |(_, endian)| endian)
+}
+
 /// Regmap Configuration
 ///
+/// Configurations can be copied, so a driver handling several similar register maps can derive
+/// their configurations from a common base.
+///
+/// # Examples
+///
+/// ```
+/// use kernel::regmap::Config;
+///
+/// const BASE: Config = Config::new(8, 8).with_max_register(0xff);
+/// const SMALL: Config = BASE.clone_with_max_register(0x16);
+///
+/// assert_eq!(BASE.max_register(), 0xff);
+/// assert_eq!(SMALL.max_register(), 0x16);
+/// ```
+///
 /// # Invariants
 ///
 /// `self.raw` always contain valid data.
+#[derive(Clone, Copy)]
 pub struct Config {
     raw: bindings::regmap_config,
+    volatile_fn: Option<fn(u32) -> bool>,
 }
 impl Config {
     /// Create a new regmap Config
@@ -263,7 +882,10 @@ pub const fn new(reg_bits: i32, val_bits: i32) -> Self {
         cfg.reg_bits = reg_bits;
         cfg.val_bits = val_bits;
 
-        Self { raw: cfg }
+        Self {
+            raw: cfg,
+            volatile_fn: None,
+        }
     }
 
     config_with!(
@@ -271,12 +893,42 @@ pub const fn new(reg_bits: i32, val_bits: i32) -> Self {
         max_register: u32
     );
 
+    /// Returns the maximum valid register address, zero if unset.
+    pub const fn max_register(&self) -> u32 {
+        self.raw.max_register
+    }
+
+    /// Returns a copy of this configuration with a different maximum valid register address.
+    pub const fn clone_with_max_register(&self, max_register: u32) -> Self {
+        Self { ..*self }.with_max_register(max_register)
+    }
+
+    config_with!(
+        /// Mask to set in the top byte of the register address when doing a read.
+        read_flag_mask: kernel::ffi::c_ulong
+    );
+
+    config_with!(
+        /// Mask to set in the top byte of the register address when doing a write.
+        write_flag_mask: kernel::ffi::c_ulong
+    );
+
     config_with!(
         /// Type of caching being performed.
         cache_type: CacheType, cache_type as _
     );
 
-    pub fn with_access_ops<T: ConfigOps>(mut self) -> Self {
+    config_with!(
+        /// Byte order of register values on the bus.
+        val_format_endian: Endian, val_format_endian as _
+    );
+
+    /// Install the readable, writeable, volatile and precious register callbacks of `T`.
+    ///
+    /// [`Config::new`] doesn't restrict register access, so this is optional. `T` is usually
+    /// the `AccessOps` type generated by [`define_regmap_field_descs`], but drivers without
+    /// field descriptors can implement [`ConfigOps`] themselves.
+    pub const fn with_access_ops<T: ConfigOps>(mut self) -> Self {
         self.raw.writeable_reg = Some(Self::writeable_reg_callback::<T>);
         self.raw.readable_reg = Some(Self::readable_reg_callback::<T>);
         self.raw.volatile_reg = Some(Self::volatile_reg_callback::<T>);
@@ -284,6 +936,126 @@ pub fn with_access_ops<T: ConfigOps>(mut self) -> Self {
         self
     }
 
+    /// Decide register volatility with `f` instead of [`ConfigOps::is_volatile_reg`].
+    ///
+    /// The C callback is only passed the device, so the regmap constructors store `f` as a
+    /// resource of the device, where the callback looks it up. A device can only have one such
+    /// function, creating a second regmap with one for the same device fails with [`EBUSY`].
+    ///
+    /// This replaces the volatile callback installed by [`Config::with_access_ops`], so call it
+    /// afterwards to combine both.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kernel::regmap::Config;
+    ///
+    /// // Interrupt status registers are cleared by reading them.
+    /// fn is_volatile(reg: u32) -> bool {
+    ///     matches!(reg, 0x4e | 0x7d)
+    /// }
+    ///
+    /// const CONFIG: Config = Config::new(8, 8).with_volatile_fn(is_volatile);
+    /// ```
+    pub const fn with_volatile_fn(mut self, f: fn(u32) -> bool) -> Self {
+        self.volatile_fn = Some(f);
+        self.raw.volatile_reg = Some(Self::volatile_fn_callback);
+        self
+    }
+
+    /// Run `init`, which creates a regmap for `dev`, with the function of
+    /// [`Config::with_volatile_fn`], if any, stored as a resource of `dev`.
+    ///
+    /// See [`init_with_volatile_fn`].
+    ///
+    /// # Safety
+    ///
+    /// `dev` must be a non-null and valid `struct device` pointer.
+    #[cfg_attr(
+        not(any(
+            CONFIG_REGMAP_I2C = "y",
+            CONFIG_REGMAP_SPI = "y",
+            CONFIG_REGMAP_MMIO = "y"
+        )),
+        allow(dead_code)
+    )]
+    unsafe fn init_with_volatile_fn<R>(
+        &self,
+        dev: *mut bindings::device,
+        init: impl FnOnce() -> Result<R>,
+    ) -> Result<R> {
+        // INVARIANT: `dev` is valid by the safety requirements of this function.
+        init_with_volatile_fn(&mut DevVolatileFn(dev), self.volatile_fn, init)
+    }
+
+    /// Store `f` as a resource of `dev`.
+    ///
+    /// # Safety
+    ///
+    /// `dev` must be a non-null and valid `struct device` pointer.
+    unsafe fn attach_volatile_fn(dev: *mut bindings::device, f: fn(u32) -> bool) -> Result {
+        // SAFETY: `volatile_fn_release` is a valid release function and `name` is a valid
+        // null-terminated string.
+        let res = unsafe {
+            bindings::__devres_alloc_node(
+                Some(Self::volatile_fn_release),
+                core::mem::size_of::<fn(u32) -> bool>(),
+                GFP_KERNEL.as_raw(),
+                bindings::NUMA_NO_NODE,
+                crate::c_str!("regmap_volatile_fn").as_char_ptr(),
+            )
+        };
+        if res.is_null() {
+            return Err(ENOMEM);
+        }
+        // SAFETY: `res` was just allocated with the size of a function pointer, and devres
+        // allocations are suitably aligned for it.
+        unsafe { res.cast::<fn(u32) -> bool>().write(f) };
+        // SAFETY: `dev` is valid and `res` is a devres allocation that isn't added yet.
+        unsafe { bindings::devres_add(dev, res) };
+        Ok(())
+    }
+
+    /// Look up the resource allocated by `attach_volatile_fn`, or null if there is none.
+    ///
+    /// # Safety
+    ///
+    /// `dev` must be a non-null and valid `struct device` pointer.
+    unsafe fn find_volatile_fn(dev: *mut bindings::device) -> *mut kernel::ffi::c_void {
+        // SAFETY: `dev` is valid by the safety requirements of this function.
+        unsafe {
+            bindings::devres_find(
+                dev,
+                Some(Self::volatile_fn_release),
+                None,
+                core::ptr::null_mut(),
+            )
+        }
+    }
+
+    /// The resource holds a plain function pointer, so there is nothing to release.
+    unsafe extern "C" fn volatile_fn_release(
+        _dev: *mut bindings::device,
+        _res: *mut kernel::ffi::c_void,
+    ) {
+    }
+
+    /// # Safety
+    ///
+    /// `dev` must be a non-null and valid `struct device` pointer.
+    unsafe extern "C" fn volatile_fn_callback(dev: *mut bindings::device, reg: u32) -> bool {
+        // SAFETY: `dev` is valid by the safety requirements of this function.
+        let res = unsafe { Self::find_volatile_fn(dev) };
+        let f = if res.is_null() {
+            None
+        } else {
+            // SAFETY: Resources with `volatile_fn_release` are only allocated by
+            // `attach_volatile_fn`, which initializes them with a function pointer.
+            Some(unsafe { *res.cast::<fn(u32) -> bool>() })
+        };
+        volatile_with(f, reg)
+    }
+
     /// # Safety
     ///
     /// `_dev` must be a non-null and valid `struct device` pointer.
@@ -325,6 +1097,86 @@ pub fn with_access_ops<T: ConfigOps>(mut self) -> Self {
     }
 }
 
+/// Whether `reg` is volatile according to `f`.
+///
+/// Without a function, e.g. if the regmap outlives the device resource holding it, registers are
+/// treated as volatile, which always goes to the hardware.
+fn volatile_with(f: Option<fn(u32) -> bool>, reg: u32) -> bool {
+    match f {
+        Some(f) => f(reg),
+        None => true,
+    }
+}
+
+/// Storage of the function of [`Config::with_volatile_fn`] for a device.
+trait VolatileFnRes {
+    /// Whether a function is already stored.
+    fn find(&self) -> bool;
+    /// Store `f`.
+    fn add(&mut self, f: fn(u32) -> bool) -> Result;
+    /// Remove the stored function.
+    fn release(&mut self);
+}
+
+/// The function of [`Config::with_volatile_fn`] stored as a device resource.
+///
+/// # Invariants
+///
+/// The pointer is a non-null and valid `struct device` pointer.
+struct DevVolatileFn(*mut bindings::device);
+
+impl VolatileFnRes for DevVolatileFn {
+    fn find(&self) -> bool {
+        // SAFETY: `self.0` is valid by the type invariants.
+        !unsafe { Config::find_volatile_fn(self.0) }.is_null()
+    }
+
+    fn add(&mut self, f: fn(u32) -> bool) -> Result {
+        // SAFETY: `self.0` is valid by the type invariants.
+        unsafe { Config::attach_volatile_fn(self.0, f) }
+    }
+
+    fn release(&mut self) {
+        // SAFETY: `self.0` is valid by the type invariants and `volatile_fn_release` matches the
+        // resource allocated by `attach_volatile_fn`.
+        unsafe {
+            bindings::devres_release(
+                self.0,
+                Some(Config::volatile_fn_release),
+                None,
+                core::ptr::null_mut(),
+            )
+        };
+    }
+}
+
+/// Run `init` with `f`, if any, stored in `res`.
+///
+/// Only one function can be stored per device, so this fails with `EBUSY` if there already is one.
+/// If `init` fails, the stored function is removed again so that a later attempt can succeed.
+#[cfg_attr(
+    not(any(
+        CONFIG_REGMAP_I2C = "y",
+        CONFIG_REGMAP_SPI = "y",
+        CONFIG_REGMAP_MMIO = "y"
+    )),
+    allow(dead_code)
+)]
+fn init_with_volatile_fn<R>(
+    res: &mut impl VolatileFnRes,
+    f: Option<fn(u32) -> bool>,
+    init: impl FnOnce() -> Result<R>,
+) -> Result<R> {
+    let Some(f) = f else {
+        return init();
+    };
+    if res.find() {
+        return Err(EBUSY);
+    }
+    res.add(f)?;
+    init().inspect_err(|_| res.release())
+}
+
 /// Definitions describing how registers can be accessed.
 pub mod access {
     /// Register can be read from.
@@ -349,11 +1201,31 @@ macro_rules! regmap_check_access {
             return true;
         }
     };
+
+    ($type:ident, $access:expr, $reg:ident, field: $reg_name:ident::$field_name:ident) => {
+        if kernel::regmap::access::$type & $access > 0
+            && kernel::regmap::reg_field_bank_contains(&$reg_name::$field_name::reg_field(), $reg)
+        {
+            return true;
+        }
+    };
 }
 // macro use only
 #[doc(hidden)]
 pub use regmap_check_access;
 
+// macro use only
+#[doc(hidden)]
+/// Returns whether `reg` is one of the registers following the base register of the indexed
+/// field `field`.
+pub const fn reg_field_bank_contains(field: &bindings::reg_field, reg: u32) -> bool {
+    if field.id_size == 0 || reg <= field.reg {
+        return false;
+    }
+    let delta = reg - field.reg;
+    field.id_offset != 0 && delta % field.id_offset == 0 && delta / field.id_offset < field.id_size
+}
+
 /// Common operations for all field types
 pub trait FieldCommonOps {
     /// Get the Mask for the field
@@ -451,7 +1323,7 @@ fn force_update_bits<const N: usize>(
 ///
 /// # Syntax
 ///
-/// `bit(index, access)`
+/// `bit(index, access[, [id_size; id_offset]])`
 ///
 /// where
 /// * `index`: bit index starting from 0
@@ -460,6 +1332,7 @@ fn force_update_bits<const N: usize>(
 ///     - `wo`: write-only ([`BitFieldWriteOps`] gets implemented)
 ///     - `rw`: read and write (both [`BitFieldReadOps`] and [`BitFieldWriteOps`] gets
 ///         implemented)
+/// * `[id_size; id_offset]`: optional, makes the field indexed, see [`regmap_field_raw`].
 ///
 /// # Examples
 ///
@@ -478,33 +1351,40 @@ fn force_update_bits<const N: usize>(
 /// ```
 #[macro_export]
 macro_rules! regmap_field_bit {
-    ($field_name:ident, $access: expr, $reg:literal, $pos:literal, rw) => {
+    ($field_name:ident, $access: expr, $reg:literal, $pos:literal, rw
+        $(, [$id_size:literal; $id_offset:literal])?) => {
         kernel::static_assert!($access & kernel::regmap::access::RW == kernel::regmap::access::RW);
 
-        $crate::regmap_field_bit!($field_name, $reg, $pos, reserved);
+        $crate::regmap_field_bit!($field_name, $reg, $pos, reserved
+            $(, [$id_size; $id_offset])?);
         $crate::regmap_field_bit!($field_name, _ro);
         $crate::regmap_field_bit!($field_name, _wo);
     };
 
-    ($field_name:ident, $access: expr, $reg:literal, $pos:literal, ro) => {
+    ($field_name:ident, $access: expr, $reg:literal, $pos:literal, ro
+        $(, [$id_size:literal; $id_offset:literal])?) => {
         kernel::static_assert!(
             $access & kernel::regmap::access::READ == kernel::regmap::access::READ
         );
 
-        $crate::regmap_field_bit!($field_name, $reg, $pos, reserved);
+        $crate::regmap_field_bit!($field_name, $reg, $pos, reserved
+            $(, [$id_size; $id_offset])?);
         $crate::regmap_field_bit!($field_name, _ro);
     };
 
-    ($field_name:ident, $access: expr, $reg:literal, $pos:literal, wo) => {
+    ($field_name:ident, $access: expr, $reg:literal, $pos:literal, wo
+        $(, [$id_size:literal; $id_offset:literal])?) => {
         kernel::static_assert!(
             $access & kernel::regmap::access::WRITE == kernel::regmap::access::WRITE
         );
 
-        $crate::regmap_field_bit!($field_name, $reg, $pos, reserved);
+        $crate::regmap_field_bit!($field_name, $reg, $pos, reserved
+            $(, [$id_size; $id_offset])?);
         $crate::regmap_field_bit!($field_name, _wo);
     };
 
-    ($field_name:ident, $reg:literal, $pos:literal, reserved) => {
+    ($field_name:ident, $reg:literal, $pos:literal, reserved
+        $(, [$id_size:literal; $id_offset:literal])?) => {
         kernel::macros::paste! {
             struct [<_Bit $pos >];
         }
@@ -515,8 +1395,8 @@ pub(crate) const fn reg_field() -> bindings::reg_field {
                     reg: $reg,
                     lsb: $pos,
                     msb: $pos + 1,
-                    id_offset: 0,
-                    id_size: 0,
+                    id_offset: 0 $(+ $id_offset)?,
+                    id_size: 0 $(+ $id_size)?,
                 }
             }
 
@@ -583,7 +1463,7 @@ fn force_clear<const N: usize>(fields: &mut regmap::Fields<N>) -> Result {
 ///
 /// # Syntax
 ///
-/// `enum(bits_range, access, { variant_definitions })`
+/// `enum(bits_range, access, [[id_size; id_offset],] { variant_definitions })`
 ///
 /// where
 /// * `bits_range`: bit used to store the data.
@@ -592,6 +1472,7 @@ fn force_clear<const N: usize>(fields: &mut regmap::Fields<N>) -> Result {
 ///     - `wo`: write-only ([`EnumFieldWriteOps`] gets implemented)
 ///     - `rw`: read and write (both [`EnumFieldReadOps`] and [`EnumFieldWriteOps`] gets
 ///         implemented)
+/// * `[id_size; id_offset]`: optional, makes the field indexed, see [`regmap_field_raw`].
 /// * `variant_definitions`: list of all the enum variants using the syntax: `VariantName = Value,`.
 ///
 /// # Examples
@@ -613,37 +1494,40 @@ fn force_clear<const N: usize>(fields: &mut regmap::Fields<N>) -> Result {
 /// ```
 #[macro_export]
 macro_rules! regmap_field_enum {
-    ($field_name:ident, $access: expr, $reg:literal, [$msb:literal:$lsb:literal], ro, {
-        $($k:ident = $v:literal,)+ }) => {
+    ($field_name:ident, $access: expr, $reg:literal, [$msb:literal:$lsb:literal], ro,
+        $([$id_size:literal; $id_offset:literal],)? { $($k:ident = $v:literal,)+ }) => {
         kernel::static_assert!(
             $access & kernel::regmap::access::READ == kernel::regmap::access::READ
         );
 
-        $crate::regmap_field_enum!($field_name, $reg, [$msb:$lsb], reserved, { $($k = $v,)+ });
+        $crate::regmap_field_enum!($field_name, $reg, [$msb:$lsb], reserved,
+            $([$id_size; $id_offset],)? { $($k = $v,)+ });
         $crate::regmap_field_enum!($field_name, _ro);
     };
 
-    ($field_name:ident, $access: expr, $reg:literal, [$msb:literal:$lsb:literal], rw, {
-        $($k:ident = $v:literal,)+ }) => {
+    ($field_name:ident, $access: expr, $reg:literal, [$msb:literal:$lsb:literal], rw,
+        $([$id_size:literal; $id_offset:literal],)? { $($k:ident = $v:literal,)+ }) => {
         kernel::static_assert!($access & kernel::regmap::access::RW == kernel::regmap::access::RW);
 
-        $crate::regmap_field_enum!($field_name, $reg, [$msb:$lsb], reserved, { $($k = $v,)+ });
+        $crate::regmap_field_enum!($field_name, $reg, [$msb:$lsb], reserved,
+            $([$id_size; $id_offset],)? { $($k = $v,)+ });
         $crate::regmap_field_enum!($field_name, _ro);
         $crate::regmap_field_enum!($field_name, _wo);
     };
 
-    ($field_name:ident, $access: expr, $reg:literal, [$msb:literal:$lsb:literal], wo, {
-        $($k:ident = $v:literal,)+ }) => {
+    ($field_name:ident, $access: expr, $reg:literal, [$msb:literal:$lsb:literal], wo,
+        $([$id_size:literal; $id_offset:literal],)? { $($k:ident = $v:literal,)+ }) => {
         kernel::static_assert!(
             $access & kernel::regmap::access::WRITE == kernel::regmap::access::WRITE
         );
 
-        $crate::regmap_field_enum!($field_name, $reg, [$msb:$lsb], reserved, { $($k = $v,)+ });
+        $crate::regmap_field_enum!($field_name, $reg, [$msb:$lsb], reserved,
+            $([$id_size; $id_offset],)? { $($k = $v,)+ });
         $crate::regmap_field_enum!($field_name, _wo);
     };
 
-    ($field_name:ident, $reg:literal, [$msb:literal:$lsb:literal], reserved, {
-        $($k:ident = $v:literal,)+ }) => {
+    ($field_name:ident, $reg:literal, [$msb:literal:$lsb:literal], reserved,
+        $([$id_size:literal; $id_offset:literal],)? { $($k:ident = $v:literal,)+ }) => {
         kernel::macros::paste! {
             #[repr(u32)]
             #[allow(non_camel_case_types)]
@@ -668,8 +1552,8 @@ pub(crate) const fn reg_field() -> bindings::reg_field {
                         reg: $reg,
                         lsb: $lsb,
                         msb: $msb,
-                        id_offset: 0,
-                        id_size: 0,
+                        id_offset: 0 $(+ $id_offset)?,
+                        id_size: 0 $(+ $id_size)?,
                     }
                 }
 
@@ -727,7 +1611,7 @@ fn force_write<const N: usize>(
 ///
 /// # Syntax
 ///
-/// `raw(bits_range, access)`
+/// `raw(bits_range, access[, [id_size; id_offset]])`
 ///
 /// where
 /// * `bits_range`: bits used to store the data.
@@ -736,6 +1620,10 @@ fn force_write<const N: usize>(
 ///     - `wo`: write-only ([`RawFieldWriteOps`] gets implemented)
 ///     - `rw`: read and write (both [`RawFieldReadOps`] and [`RawFieldWriteOps`] gets
 ///         implemented)
+/// * `[id_size; id_offset]`: optional, makes the field indexed. It then describes `id_size`
+///   identical fields, each in the register `id_offset` after the previous one. The register
+///   access permission applies to all of them. The field operations access the first one,
+///   use [`Fields::read_indexed`] and [`Fields::write_indexed`] for the others.
 ///
 /// # Examples
 ///
@@ -750,43 +1638,60 @@ fn force_write<const N: usize>(
 /// pid::value::read(&mut fields);
 /// progvsel1::voutvsel1::write(&mut fields, 0x42);
 /// ```
+///
+/// Eight consecutive slave ID registers, from `0x5d` to `0x64`, can be described as:
+///
+/// ```ignore
+/// regmap::define_regmap_field_descs!(FIELD_DESCS, {
+///     (slave_id, 0x5d, RW, { id => raw([7:1], rw, [8; 1]) })
+/// });
+///
+/// fields.write_indexed(register::slave_id::id::id() as usize, 3, 0x36)?;
+/// ```
 #[macro_export]
 macro_rules! regmap_field_raw {
-    ($field_name:ident, $access: expr, $reg:literal, [$msb:literal:$lsb:literal], rw) => {
+    ($field_name:ident, $access: expr, $reg:literal, [$msb:literal:$lsb:literal], rw
+        $(, [$id_size:literal; $id_offset:literal])?) => {
         kernel::static_assert!($access & kernel::regmap::access::RW == kernel::regmap::access::RW);
 
-        $crate::regmap_field_raw!($field_name, $reg, [$msb:$lsb], reserved);
+        $crate::regmap_field_raw!($field_name, $reg, [$msb:$lsb], reserved
+            $(, [$id_size; $id_offset])?);
         $crate::regmap_field_raw!($field_name, $reg, [$msb:$lsb], _ro);
         $crate::regmap_field_raw!($field_name, $reg, [$msb:$lsb], _wo);
     };
 
-    ($field_name:ident, $access: expr, $reg:literal, [$msb:literal:$lsb:literal], ro) => {
+    ($field_name:ident, $access: expr, $reg:literal, [$msb:literal:$lsb:literal], ro
+        $(, [$id_size:literal; $id_offset:literal])?) => {
         kernel::static_assert!(
             $access & kernel::regmap::access::READ == kernel::regmap::access::READ
         );
 
-        $crate::regmap_field_raw!($field_name, $reg, [$msb:$lsb], reserved);
+        $crate::regmap_field_raw!($field_name, $reg, [$msb:$lsb], reserved
+            $(, [$id_size; $id_offset])?);
         $crate::regmap_field_raw!($field_name, $reg, [$msb:$lsb], _ro);
     };
 
-    ($field_name:ident, $access: expr, $reg:literal, [$msb:literal:$lsb:literal], wo) => {
+    ($field_name:ident, $access: expr, $reg:literal, [$msb:literal:$lsb:literal], wo
+        $(, [$id_size:literal; $id_offset:literal])?) => {
         kernel::static_assert!(
             $access & kernel::regmap::access::WRITE == kernel::regmap::access::WRITE
         );
 
-        $crate::regmap_field_raw!($field_name, $reg, [$msb:$lsb], reserved);
+        $crate::regmap_field_raw!($field_name, $reg, [$msb:$lsb], reserved
+            $(, [$id_size; $id_offset])?);
         $crate::regmap_field_raw!($field_name, $reg, [$msb:$lsb], _wo);
     };
 
-    ($field_name:ident, $reg:literal, [$msb:literal:$lsb:literal], reserved) => {
+    ($field_name:ident, $reg:literal, [$msb:literal:$lsb:literal], reserved
+        $(, [$id_size:literal; $id_offset:literal])?) => {
         impl $field_name {
             pub(crate) const fn reg_field() -> bindings::reg_field {
                 bindings::reg_field {
                     reg: $reg,
                     lsb: $lsb,
                     msb: $msb,
-                    id_offset: 0,
-                    id_size: 0,
+                    id_offset: 0 $(+ $id_offset)?,
+                    id_size: 0 $(+ $id_size)?,
                 }
             }
 
@@ -1023,6 +1928,11 @@ fn is_readable_reg(reg: u32) -> bool {
                         $(
                             kernel::regmap::regmap_check_access!(READ, $access, reg, $reg_addr);
                         )+
+                        $($(
+                            kernel::regmap::regmap_check_access!(
+                                READ, $access, reg, field: $reg_name::$field_name
+                            );
+                        )*)+
 
                         false
                     }
@@ -1031,6 +1941,11 @@ fn is_writeable_reg(reg: u32) -> bool {
                         $(
                             kernel::regmap::regmap_check_access!(WRITE, $access, reg, $reg_addr);
                         )+
+                        $($(
+                            kernel::regmap::regmap_check_access!(
+                                WRITE, $access, reg, field: $reg_name::$field_name
+                            );
+                        )*)+
 
                         false
                     }
@@ -1039,6 +1954,11 @@ fn is_volatile_reg(reg: u32) -> bool {
                         $(
                             kernel::regmap::regmap_check_access!(VOLATILE, $access, reg, $reg_addr);
                         )+
+                        $($(
+                            kernel::regmap::regmap_check_access!(
+                                VOLATILE, $access, reg, field: $reg_name::$field_name
+                            );
+                        )*)+
 
                         false
                     }
@@ -1047,6 +1967,11 @@ fn is_precious_reg(reg: u32) -> bool {
                         $(
                             kernel::regmap::regmap_check_access!(PRECIOUS, $access, reg, $reg_addr);
                         )+
+                        $($(
+                            kernel::regmap::regmap_check_access!(
+                                PRECIOUS, $access, reg, field: $reg_name::$field_name
+                            );
+                        )*)+
 
                         false
                     }
@@ -1065,3 +1990,184 @@ fn is_precious_reg(reg: u32) -> bool {
     };
 }
 pub use define_regmap_field_descs;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::delay::{Clock, MockClock};
+    use core::cell::Cell;
+
+    #[test]
+    fn test_with_volatile_fn() {
+        let config = Config::new(8, 8).with_volatile_fn(|reg| reg == 0x4e);
+        assert!(config.raw.volatile_reg.is_some());
+
+        assert!(volatile_with(config.volatile_fn, 0x4e));
+        assert!(!volatile_with(config.volatile_fn, 0x4f));
+        assert!(volatile_with(None, 0x4f));
+    }
+
+    #[derive(Default)]
+    struct MockRes {
+        f: Option<fn(u32) -> bool>,
+        adds: usize,
+    }
+
+    impl VolatileFnRes for MockRes {
+        fn find(&self) -> bool {
+            self.f.is_some()
+        }
+
+        fn add(&mut self, f: fn(u32) -> bool) -> Result {
+            self.f = Some(f);
+            self.adds += 1;
+            Ok(())
+        }
+
+        fn release(&mut self) {
+            self.f = None;
+        }
+    }
+
+    #[test]
+    fn test_init_with_volatile_fn() {
+        let f: fn(u32) -> bool = |reg| reg == 0x4e;
+        let mut res = MockRes::default();
+
+        assert_eq!(init_with_volatile_fn(&mut res, Some(f), || Ok(1)), Ok(1));
+        assert!(res.find());
+
+        // A second regmap for the same device would replace the function of the first one.
+        let called = Cell::new(false);
+        let second = init_with_volatile_fn(&mut res, Some(f), || {
+            called.set(true);
+            Ok(2)
+        });
+        assert_eq!(second, Err(EBUSY));
+        assert!(!called.get());
+        assert_eq!(res.adds, 1);
+        assert!(res.find());
+
+        // Without a function, nothing is stored, so there is no conflict.
+        assert_eq!(init_with_volatile_fn(&mut res, None, || Ok(3)), Ok(3));
+        assert_eq!(res.adds, 1);
+    }
+
+    #[test]
+    fn test_init_with_volatile_fn_error() {
+        let f: fn(u32) -> bool = |reg| reg == 0x4e;
+        let mut res = MockRes::default();
+
+        assert_eq!(
+            init_with_volatile_fn::<i32>(&mut res, Some(f), || Err(ENODEV)),
+            Err(ENODEV)
+        );
+        assert!(!res.find());
+
+        // The failed attempt doesn't leave its function behind.
+        assert_eq!(init_with_volatile_fn(&mut res, Some(f), || Ok(1)), Ok(1));
+        assert_eq!(res.adds, 2);
+    }
+
+    #[test]
+    fn test_reg_field_bank_contains() {
+        let bank = bindings::reg_field {
+            reg: 0x5d,
+            lsb: 1,
+            msb: 7,
+            id_offset: 1,
+            id_size: 8,
+        };
+        assert!(reg_field_bank_contains(&bank, 0x5e));
+        assert!(reg_field_bank_contains(&bank, 0x64));
+        assert!(!reg_field_bank_contains(&bank, 0x5c));
+        assert!(!reg_field_bank_contains(&bank, 0x65));
+
+        let strided = bindings::reg_field {
+            id_offset: 2,
+            id_size: 2,
+            ..bank
+        };
+        assert!(reg_field_bank_contains(&strided, 0x5f));
+        assert!(!reg_field_bank_contains(&strided, 0x5e));
+        assert!(!reg_field_bank_contains(&strided, 0x61));
+
+        let single = bindings::reg_field {
+            id_offset: 0,
+            id_size: 0,
+            ..bank
+        };
+        assert!(!reg_field_bank_contains(&single, 0x5e));
+    }
+
+    #[test]
+    fn test_reg_sequence_keeps_order() {
+        let seq = reg_sequence(&[(0x10, 0x01), (0x11, 0x80), (0x10, 0x03)]).unwrap();
+
+        let pairs: [(u32, u32); 3] = core::array::from_fn(|i| (seq[i].reg, seq[i].def));
+        assert_eq!(pairs, [(0x10, 0x01), (0x11, 0x80), (0x10, 0x03)]);
+        assert!(seq.iter().all(|entry| entry.delay_us == 0));
+    }
+
+    #[test]
+    fn test_wait_for_bit_becomes_set() {
+        let clock = MockClock(Cell::new(0));
+        let mut reads = 0;
+
+        let res = wait_for_bit_with(
+            &clock,
+            || {
+                reads += 1;
+                Ok(if reads < 5 { 0x01 } else { 0x09 })
+            },
+            3,
+            true,
+            10_000,
+        );
+
+        assert_eq!(res, Ok(()));
+        assert_eq!(reads, 5);
+    }
+
+    #[test]
+    fn test_wait_for_bit_never_set() {
+        let clock = MockClock(Cell::new(0));
+
+        let res = wait_for_bit_with(&clock, || Ok(0xf7), 3, true, 10_000);
+
+        assert_eq!(res, Err(ETIMEDOUT));
+        assert_eq!(clock.now_us(), 10_000);
+
+        // The timeout isn't rounded up to whole milliseconds.
+        let clock = MockClock(Cell::new(0));
+        let mut reads = 0;
+
+        let res = wait_for_bit_with(
+            &clock,
+            || {
+                reads += 1;
+                Ok(0xf7)
+            },
+            3,
+            true,
+            250,
+        );
+
+        assert_eq!(res, Err(ETIMEDOUT));
+        assert_eq!(clock.now_us(), 300);
+        assert_eq!(reads, 4);
+    }
+
+    #[test]
+    fn test_wait_for_bit_cleared() {
+        let clock = MockClock(Cell::new(0));
+
+        let res = wait_for_bit_with(&clock, || Ok(0xf7), 3, false, 0);
+
+        assert_eq!(res, Ok(()));
+        assert_eq!(
+            wait_for_bit_with(&clock, || Ok(0), 32, false, 0),
+            Err(EINVAL)
+        );
+    }
+}
@@ -6,11 +6,13 @@
 
 use crate::{
     bindings,
+    device::Device,
     error::{code::*, Error, Result},
+    str::CStr,
 };
 
 /// [`driver::Device`] operating modes
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 #[repr(u32)]
 pub enum Mode {
     /// Invalid mode
@@ -42,3 +44,40 @@ fn try_from(mode: kernel::ffi::c_uint) -> Result<Self> {
         }
     }
 }
+
+impl Mode {
+    /// Convert a mode name as used in device trees into a [`Mode`]
+    ///
+    /// The valid names are `"fast"`, `"normal"`, `"idle"` and `"standby"`, [`EINVAL`] is returned
+    /// for anything else.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kernel::{c_str, regulator::Mode};
+    ///
+    /// assert_eq!(Mode::from_dt_str(c_str!("fast")), Ok(Mode::Fast));
+    /// assert_eq!(Mode::from_dt_str(c_str!("normal")), Ok(Mode::Normal));
+    /// assert_eq!(Mode::from_dt_str(c_str!("idle")), Ok(Mode::Idle));
+    /// assert_eq!(Mode::from_dt_str(c_str!("standby")), Ok(Mode::Standby));
+    /// assert_eq!(Mode::from_dt_str(c_str!("turbo")), Err(EINVAL));
+    /// ```
+    pub fn from_dt_str(s: &CStr) -> Result<Self> {
+        match s.as_bytes() {
+            b"fast" => Ok(Self::Fast),
+            b"normal" => Ok(Self::Normal),
+            b"idle" => Ok(Self::Idle),
+            b"standby" => Ok(Self::Standby),
+            _ => Err(EINVAL),
+        }
+    }
+}
+
+impl Device {
+    /// Read the firmware property `name` as a regulator [`Mode`] name
+    ///
+    /// See [`Mode::from_dt_str`] for the valid names.
+    pub fn property_read_regulator_mode(&self, name: &CStr) -> Result<Mode> {
+        Mode::from_dt_str(&self.as_fwnode().property_read_string(name)?)
+    }
+}
@@ -33,9 +33,14 @@
 //! }
 //! ```
 
+#[cfg(CONFIG_GPIOLIB)]
+use crate::gpio::consumer as gpio;
+#[cfg(CONFIG_REGMAP)]
+use crate::regmap::Regmap;
 use crate::{
+    alloc::{flags::GFP_KERNEL, KVec},
     device,
-    error::{code::*, from_err_ptr, from_result, Error, Result},
+    error::{code::*, from_err_ptr, from_result, to_result, Error, Result},
     macros::vtable,
     private::Sealed,
     regulator::Mode,
@@ -44,8 +49,6 @@
     types::ForeignOwnable,
     ThisModule,
 };
-#[cfg(CONFIG_REGMAP)]
-use crate::{error::to_result, regmap::Regmap};
 use core::{marker::PhantomData, mem::ManuallyDrop, ptr::NonNull};
 
 #[cfg(not(CONFIG_REGMAP))]
@@ -174,6 +177,52 @@ fn set_active_discharge(_rdev: &mut Device<Self::Data>, _enable: bool) -> Result
         Err(ENOTSUPP)
     }
 
+    /// Configure the over-current protection of the regulator.
+    ///
+    /// `lim_ua` is the current limit in microampere, zero means the driver should keep its
+    /// default limit. `severity` is one of the `REGULATOR_SEVERITY_*` constants and tells whether
+    /// hitting the limit should shut down the regulator, be reported as an error or as a warning.
+    ///
+    /// The operation is only installed in the regulator's vtable if it is implemented:
+    ///
+    /// ```
+    /// use kernel::regulator::driver::{Device, Driver};
+    ///
+    /// struct NoOcp;
+    ///
+    /// #[vtable]
+    /// impl Driver for NoOcp {
+    ///     type Data = ();
+    /// }
+    ///
+    /// struct Ocp;
+    ///
+    /// #[vtable]
+    /// impl Driver for Ocp {
+    ///     type Data = ();
+    ///
+    ///     fn set_over_current_protection(
+    ///         _rdev: &mut Device<Self::Data>,
+    ///         _lim_ua: i32,
+    ///         _severity: u32,
+    ///         _enable: bool,
+    ///     ) -> Result {
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// assert!(!NoOcp::HAS_SET_OVER_CURRENT_PROTECTION);
+    /// assert!(Ocp::HAS_SET_OVER_CURRENT_PROTECTION);
+    /// ```
+    fn set_over_current_protection(
+        _rdev: &mut Device<Self::Data>,
+        _lim_ua: i32,
+        _severity: u32,
+        _enable: bool,
+    ) -> Result {
+        Err(ENOTSUPP)
+    }
+
     /// Configure the regulator as enabled.
     fn enable(_rdev: &mut Device<Self::Data>) -> Result {
         Err(ENOTSUPP)
@@ -255,8 +304,10 @@ fn set_suspend_mode(_rdev: &mut Device<Self::Data>, _mode: Mode) -> Result {
 ///
 /// # Invariants
 ///
-/// `self.0` has always valid data.
-pub struct Desc(bindings::regulator_desc);
+/// `self.0` has always valid data. `Desc` is `repr(C)` so a pointer to `self.0` is also a
+/// pointer to the `Desc`.
+#[repr(C)]
+pub struct Desc(bindings::regulator_desc, Rounding);
 impl Desc {
     /// Create a new [`Device`] descriptor
     pub const fn new<T: Driver>(name: &'static CStr, reg_type: Type) -> Self {
@@ -268,7 +319,7 @@ pub const fn new<T: Driver>(name: &'static CStr, reg_type: Type) -> Self {
             Type::Current => bindings::regulator_type_REGULATOR_CURRENT,
         };
         desc.ops = Adapter::<T>::build();
-        Self(desc)
+        Self(desc, Rounding::Nearest)
     }
 
     /// Setup the register address, mask, and {en,dis}able values
@@ -308,6 +359,7 @@ pub const fn with_csel(mut self, reg: u32, mask: u32, table: &'static [u32]) ->
         self.0.csel_reg = reg;
         self.0.csel_mask = mask;
         self.0.curr_table = table.as_ptr();
+        self.0.n_current_limits = table.len() as _;
         self
     }
 
@@ -330,6 +382,23 @@ pub const fn with_linear_mapping(
         self
     }
 
+    /// Set how [`Device::map_voltage_linear`] rounds a voltage that falls between two selectors.
+    ///
+    /// Defaults to [`Rounding::Nearest`].
+    pub const fn with_voltage_rounding(mut self, rounding: Rounding) -> Self {
+        self.1 = rounding;
+        self
+    }
+
+    /// Set the rate at which the output voltage changes, in microvolt per microsecond.
+    ///
+    /// The regulator core waits accordingly after changing the voltage through it, drivers
+    /// changing the selector themselves can use [`RegmapHelpers::set_voltage_sel_and_wait`].
+    pub const fn with_ramp_delay(mut self, uv_per_us: u32) -> Self {
+        self.0.ramp_delay = uv_per_us;
+        self
+    }
+
     /// Set the regulator owner
     pub const fn with_owner(mut self, owner: &'static ThisModule) -> Self {
         self.0.owner = owner.as_ptr();
@@ -372,6 +441,8 @@ pub struct Config<T: ForeignOwnable + Send + Sync = ()> {
     cfg: bindings::regulator_config,
     data: T,
     regmap: Option<Arc<Regmap>>,
+    #[cfg(CONFIG_GPIOLIB)]
+    ena_gpiod: Option<gpio::Desc>,
 }
 
 impl<T: ForeignOwnable + Send + Sync> Config<T> {
@@ -384,6 +455,8 @@ pub fn new(dev: &device::Device, data: T) -> Self {
             },
             data,
             regmap: None,
+            #[cfg(CONFIG_GPIOLIB)]
+            ena_gpiod: None,
         }
     }
 
@@ -393,6 +466,30 @@ pub fn with_regmap(mut self, regmap: Arc<Regmap>) -> Self {
         self.regmap = Some(regmap);
         self
     }
+
+    /// Use a GPIO to enable and disable the regulator.
+    ///
+    /// Ownership of `desc` is transferred to the regulator framework in [`Device::register`],
+    /// which releases the GPIO when the regulator is unregistered or if the registration fails.
+    /// If the config is dropped without being registered, the GPIO is released right away.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kernel::{c_str, device, gpio::consumer as gpio};
+    /// use kernel::regulator::driver::{Config, Desc, Device};
+    ///
+    /// fn register(dev: &device::Device, desc: &'static Desc) -> Result<Device<()>> {
+    ///     let enable = gpio::Desc::get(dev, c_str!("enable"), gpio::Flags::OutLow)?;
+    ///     let config = Config::new(dev, ()).with_enable_gpio(enable);
+    ///     Device::register(dev, desc, config)
+    /// }
+    /// ```
+    #[cfg(CONFIG_GPIOLIB)]
+    pub fn with_enable_gpio(mut self, desc: gpio::Desc) -> Self {
+        self.ena_gpiod = Some(desc);
+        self
+    }
 }
 
 /// Regulator device
@@ -439,6 +536,12 @@ pub fn register(
             config.cfg.regmap = regmap.as_raw() as _;
         };
 
+        // The regulator framework takes over the GPIO, even if the registration fails.
+        #[cfg(CONFIG_GPIOLIB)]
+        if let Some(ena_gpiod) = config.ena_gpiod.take() {
+            config.cfg.ena_gpiod = ena_gpiod.into_raw();
+        }
+
         // SAFETY: By the type invariants, we know that `dev.as_ref().as_raw()` is always
         // valid and non-null, and the descriptor and config are guaranteed to be valid values,
         // hence it is safe to perform the FFI call.
@@ -464,6 +567,149 @@ pub fn list_voltage_linear(&self, selector: u32) -> Result<i32> {
         Ok(ret)
     }
 
+    /// Map a voltage range to a selector when the regulator is using linear mapping
+    ///
+    /// Unlike the C `regulator_map_voltage_linear`, which always rounds up, `min_uv` is rounded
+    /// as configured with [`Desc::with_voltage_rounding`]. Returns [`EINVAL`] if the rounded
+    /// voltage is above `max_uv` or past the last selector.
+    pub fn map_voltage_linear(&self, min_uv: i32, max_uv: i32) -> Result<i32> {
+        // SAFETY: By the type invariants, we know that `self.rdev` is always valid and non-null.
+        // `regulator_register` guarantees that `desc` points to the descriptor the device was
+        // registered with. [`Device::register`] only registers the `regulator_desc` of a
+        // `'static` [`Desc`], which by its type invariants is at the same address.
+        let desc = unsafe { &*(*self.rdev.as_ptr()).desc.cast::<Desc>() };
+        linear_voltage_sel(
+            desc.0.min_uV,
+            desc.0.uV_step,
+            desc.0.linear_min_sel,
+            desc.0.n_voltages,
+            desc.1,
+            min_uv,
+            max_uv,
+        )
+    }
+
+    /// List all usable voltages of the regulator, in microvolt
+    ///
+    /// Calls the driver's [`Driver::list_voltage`] operation for every selector from zero to
+    /// `n_voltages - 1`. Selectors indicating a voltage that is unusable by the system (zero) are
+    /// skipped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kernel::regulator::driver::{Desc, Device, Driver, Type};
+    ///
+    /// struct MyDeviceDriver;
+    ///
+    /// #[vtable]
+    /// impl Driver for MyDeviceDriver {
+    ///     type Data = ();
+    ///
+    ///     fn list_voltage(reg: &mut Device<Self::Data>, selector: u32) -> Result<i32> {
+    ///         reg.list_voltage_linear(selector)
+    ///     }
+    /// }
+    ///
+    /// // 600mV, 700mV, 800mV and 900mV.
+    /// static DESC: Desc = Desc::new::<MyDeviceDriver>(kernel::c_str!("my_driver"), Type::Voltage)
+    ///     .with_linear_mapping(0x10, 0x3, 600_000, 100_000, 4, 0);
+    ///
+    /// fn print_voltages(reg: &Device<()>) -> Result {
+    ///     for uv in reg.list_all_voltages()?.iter() {
+    ///         pr_info!("{uv}uV\n");
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn list_all_voltages(&self) -> Result<KVec<i32>> {
+        // SAFETY: By the type invariants, we know that `self.rdev` is always valid and non-null.
+        // `regulator_register` guarantees that `desc` points to the descriptor the device was
+        // registered with, which is `'static`.
+        let desc = unsafe { &*(*self.rdev.as_ptr()).desc };
+        // SAFETY: `desc.ops` always points to the static vtable built by `Adapter::build`.
+        let list_voltage = unsafe { (*desc.ops).list_voltage }.ok_or(ENOTSUPP)?;
+
+        collect_voltages(desc.n_voltages, |selector| {
+            // SAFETY: By the type invariants, we know that `self.rdev` is always valid and
+            // non-null. The callback is safe to call with any selector values.
+            unsafe { list_voltage(self.rdev.as_ptr(), selector) }
+        })
+    }
+
+    /// List the current table of the regulator, in microampere
+    ///
+    /// This is the table set up with [`Desc::with_csel`], or an empty slice if there is none.
+    /// For [`Type::Current`] regulators, the output current is configured through
+    /// [`Driver::set_current_limit`] and [`Driver::get_current_limit`], this table enumerates
+    /// the values they can select.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use kernel::regulator::driver::{Desc, Device, Driver, RegmapHelpers, Type};
+    ///
+    /// struct MyCurrentDriver;
+    ///
+    /// #[vtable]
+    /// impl Driver for MyCurrentDriver {
+    ///     type Data = ();
+    ///
+    ///     fn get_current_limit(reg: &mut Device<Self::Data>) -> Result<i32> {
+    ///         reg.get_current_limit_regmap()
+    ///     }
+    /// }
+    ///
+    /// static DESC: Desc = Desc::new::<MyCurrentDriver>(kernel::c_str!("led"), Type::Current)
+    ///     .with_csel(0x16, 0xc0, &[3_500_000, 4_000_000, 4_500_000, 5_000_000]);
+    ///
+    /// fn max_current(reg: &Device<()>) -> Option<u32> {
+    ///     reg.list_current_table().iter().copied().max()
+    /// }
+    /// ```
+    pub fn list_current_table(&self) -> &'static [u32] {
+        // SAFETY: By the type invariants, we know that `self.rdev` is always valid and non-null.
+        // `regulator_register` guarantees that `desc` points to the descriptor the device was
+        // registered with, which is `'static`.
+        let desc = unsafe { &*(*self.rdev.as_ptr()).desc };
+        if desc.curr_table.is_null() {
+            return &[];
+        }
+        // SAFETY: `curr_table` and `n_current_limits` are only set by `Desc::with_csel` from a
+        // `'static` slice.
+        unsafe { core::slice::from_raw_parts(desc.curr_table, desc.n_current_limits as usize) }
+    }
+
+    /// Get the number of consumers currently holding the regulator enabled
+    ///
+    /// The count is read under the regulator lock, but it may change as soon as the lock is
+    /// released again.
+    pub fn enable_count(&self) -> u32 {
+        self.lock().use_count()
+    }
+
+    /// Disable the regulator output regardless of its consumers
+    ///
+    /// This is meant for emergencies, e.g. an over-temperature condition, where the output must be
+    /// switched off immediately. Like `regulator_force_disable()` on the consumer side, it holds
+    /// the regulator lock, sends `REGULATOR_EVENT_FORCE_DISABLE` notifications and calls the
+    /// driver's [`Driver::disable`] operation. The consumers' enable requests, and so
+    /// [`Device::enable_count`], are left untouched.
+    ///
+    /// Regulators switched by an enable GPIO return [`ENOTSUPP`]: the GPIO may be shared with
+    /// other regulators, and only the regulator core keeps track of its users.
+    pub fn force_disable(&self) -> Result {
+        force_disable_locked(&mut self.lock())
+    }
+
+    /// Take the regulator lock until the returned guard is dropped.
+    fn lock(&self) -> LockedRdev {
+        // SAFETY: By the type invariants, we know that `self.rdev` is always valid and non-null.
+        unsafe { bindings::regulator_lock(self.rdev.as_ptr()) };
+        // INVARIANT: The lock was taken above.
+        LockedRdev { rdev: self.rdev }
+    }
+
     /// Get regulator's name
     pub fn get_name(&self) -> &'static CStr {
         // SAFETY: By the type invariants, we know that `self.rdev` is always valid and non-null.
@@ -507,6 +753,101 @@ unsafe impl<T: ForeignOwnable + Send + Sync> Sync for Device<T> {}
 
 impl<T: ForeignOwnable + Send + Sync> Sealed for Device<T> {}
 
+/// Accesses to a regulator that are only valid while its lock is held.
+///
+/// This separates the sequence of [`Device::force_disable`] from the FFI calls.
+trait LockedAccess {
+    /// Number of consumers holding the regulator enabled.
+    fn use_count(&self) -> u32;
+
+    /// Send `event` to the regulator's notifier chain and return the combined notifier result.
+    fn notify(&mut self, event: u32) -> kernel::ffi::c_int;
+
+    /// Whether the output is switched by the driver's [`Driver::disable`] operation.
+    fn has_disable(&self) -> bool;
+
+    /// Call the driver's [`Driver::disable`] operation.
+    fn disable(&mut self) -> Result;
+}
+
+/// The steps of `_regulator_force_disable()` in the regulator core, on a locked regulator.
+fn force_disable_locked(rdev: &mut impl LockedAccess) -> Result {
+    if !rdev.has_disable() {
+        return Err(ENOTSUPP);
+    }
+
+    let ret = rdev
+        .notify(bindings::REGULATOR_EVENT_FORCE_DISABLE | bindings::REGULATOR_EVENT_PRE_DISABLE);
+    if ret & bindings::NOTIFY_STOP_MASK as kernel::ffi::c_int != 0 {
+        return Err(EINVAL);
+    }
+
+    if let Err(err) = rdev.disable() {
+        rdev.notify(
+            bindings::REGULATOR_EVENT_FORCE_DISABLE | bindings::REGULATOR_EVENT_ABORT_DISABLE,
+        );
+        return Err(err);
+    }
+
+    rdev.notify(bindings::REGULATOR_EVENT_FORCE_DISABLE | bindings::REGULATOR_EVENT_DISABLE);
+    Ok(())
+}
+
+/// A `struct regulator_dev` whose lock is held.
+///
+/// # Invariants
+///
+/// `self.rdev` is valid and locked with `regulator_lock` for the lifetime of this value.
+struct LockedRdev {
+    rdev: NonNull<bindings::regulator_dev>,
+}
+
+impl LockedAccess for LockedRdev {
+    fn use_count(&self) -> u32 {
+        // SAFETY: By the type invariants, `self.rdev` is valid and its lock, which protects
+        // `use_count`, is held.
+        unsafe { (*self.rdev.as_ptr()).use_count }
+    }
+
+    fn notify(&mut self, event: u32) -> kernel::ffi::c_int {
+        // SAFETY: By the type invariants, `self.rdev` is valid.
+        unsafe {
+            bindings::regulator_notifier_call_chain(
+                self.rdev.as_ptr(),
+                event as _,
+                core::ptr::null_mut(),
+            )
+        }
+    }
+
+    fn has_disable(&self) -> bool {
+        // SAFETY: By the type invariants, `self.rdev` is valid. `regulator_register` guarantees
+        // that `desc` points to the `'static` descriptor the device was registered with, whose
+        // `ops` point to the static vtable built by `Adapter::build`.
+        unsafe {
+            // The core switches regulators with an enable GPIO through the GPIO, which may be
+            // shared, instead of the operation.
+            (*self.rdev.as_ptr()).ena_pin.is_null()
+                && (*(*(*self.rdev.as_ptr()).desc).ops).disable.is_some()
+        }
+    }
+
+    fn disable(&mut self) -> Result {
+        // SAFETY: Same as in `has_disable`.
+        let disable = unsafe { (*(*(*self.rdev.as_ptr()).desc).ops).disable }.ok_or(ENOTSUPP)?;
+        // SAFETY: By the type invariants, `self.rdev` is valid and locked, as the operation
+        // expects.
+        to_result(unsafe { disable(self.rdev.as_ptr()) })
+    }
+}
+
+impl Drop for LockedRdev {
+    fn drop(&mut self) {
+        // SAFETY: By the type invariants, `self.rdev` is valid and locked.
+        unsafe { bindings::regulator_unlock(self.rdev.as_ptr()) };
+    }
+}
+
 /// Helper functions to implement some of the [`Driver`] trait methods using [`Regmap`].
 ///
 /// This trait is implemented by [`Device`] and is Sealed to prevent
@@ -514,9 +855,18 @@ impl<T: ForeignOwnable + Send + Sync> Sealed for Device<T> {}
 #[cfg(CONFIG_REGMAP)]
 pub trait RegmapHelpers: Sealed {
     /// Implementation of [`Driver::get_voltage_sel`] using [`Regmap`].
+    ///
+    /// Returns [`ERANGE`] if the register holds a selector past the `n_voltages` of the
+    /// descriptor, e.g. because of a corrupted bus transfer.
     fn get_voltage_sel_regmap(&self) -> Result<i32>;
     /// Implementation of [`Driver::set_voltage_sel`] using [`Regmap`].
     fn set_voltage_sel_regmap(&self, sel: u32) -> Result;
+    /// Like [`RegmapHelpers::set_voltage_sel_regmap`], but waits until the output has ramped
+    /// from the previous to the new voltage.
+    ///
+    /// The time is computed from the ramp rate set with [`Desc::with_ramp_delay`], without one
+    /// this doesn't wait at all.
+    fn set_voltage_sel_and_wait(&self, sel: u32) -> Result;
 
     /// Implementation of [`Driver::is_enabled`] using [`Regmap`].
     ///
@@ -546,6 +896,32 @@ pub trait RegmapHelpers: Sealed {
     fn get_current_limit_regmap(&self) -> Result<i32>;
 }
 
+/// Check that `sel` is a valid selector for a regulator with `n_voltages` voltages.
+///
+/// Descriptors without voltages (`n_voltages == 0`) don't restrict the selector.
+#[cfg(CONFIG_REGMAP)]
+fn check_voltage_sel(sel: i32, n_voltages: u32) -> Result<i32> {
+    match u32::try_from(sel) {
+        Ok(_) if n_voltages == 0 => Ok(sel),
+        Ok(s) if s < n_voltages => Ok(sel),
+        _ => Err(ERANGE),
+    }
+}
+
+/// Extra time [`RegmapHelpers::set_voltage_sel_and_wait`] allows the timer to fire late, like the
+/// regulator core.
+#[cfg(CONFIG_REGMAP)]
+const RAMP_DELAY_SLACK_US: u64 = 100;
+
+/// Time in microseconds an output ramping at `uv_per_us` takes from `old_uv` to `new_uv`.
+#[cfg(CONFIG_REGMAP)]
+fn ramp_delay_us(old_uv: i32, new_uv: i32, uv_per_us: u32) -> u32 {
+    if uv_per_us == 0 {
+        return 0;
+    }
+    old_uv.abs_diff(new_uv).div_ceil(uv_per_us)
+}
+
 #[cfg(CONFIG_REGMAP)]
 impl<T: ForeignOwnable + Send + Sync> RegmapHelpers for Device<T> {
     fn get_voltage_sel_regmap(&self) -> Result<i32> {
@@ -555,7 +931,11 @@ fn get_voltage_sel_regmap(&self) -> Result<i32> {
         if ret < 0 {
             return Err(Error::from_errno(ret));
         }
-        Ok(ret)
+        // SAFETY: By the type invariants, we know that `self.rdev` is always valid and non-null.
+        // `regulator_register` guarantees that `desc` points to the descriptor the device was
+        // registered with, which is `'static`.
+        let desc = unsafe { &*(*self.rdev.as_ptr()).desc };
+        check_voltage_sel(ret, desc.n_voltages)
     }
 
     fn set_voltage_sel_regmap(&self, sel: u32) -> Result {
@@ -564,6 +944,34 @@ fn set_voltage_sel_regmap(&self, sel: u32) -> Result {
         to_result(unsafe { bindings::regulator_set_voltage_sel_regmap(self.rdev.as_ptr(), sel) })
     }
 
+    fn set_voltage_sel_and_wait(&self, sel: u32) -> Result {
+        // SAFETY: By the type invariants, we know that `self.rdev` is always valid and non-null.
+        // `regulator_register` guarantees that `desc` points to the descriptor the device was
+        // registered with, which is `'static`.
+        let desc = unsafe { &*(*self.rdev.as_ptr()).desc };
+        if desc.ramp_delay == 0 {
+            return self.set_voltage_sel_regmap(sel);
+        }
+        // SAFETY: `desc.ops` always points to the static vtable built by `Adapter::build`.
+        let list_voltage = unsafe { (*desc.ops).list_voltage }.ok_or(ENOTSUPP)?;
+        let voltage = |sel: u32| {
+            // SAFETY: By the type invariants, we know that `self.rdev` is always valid and
+            // non-null. The callback is safe to call with any selector values.
+            let ret = unsafe { list_voltage(self.rdev.as_ptr(), sel) };
+            to_result(ret).map(|()| ret)
+        };
+
+        let old_uv = voltage(self.get_voltage_sel_regmap()? as u32)?;
+        let new_uv = voltage(sel)?;
+        self.set_voltage_sel_regmap(sel)?;
+
+        let delay = ramp_delay_us(old_uv, new_uv, desc.ramp_delay);
+        if delay > 0 {
+            crate::delay::usleep_range(delay.into(), u64::from(delay) + RAMP_DELAY_SLACK_US);
+        }
+        Ok(())
+    }
+
     fn is_enabled_regmap(&self) -> Result<bool> {
         // SAFETY: The type invariants guarantee that `self.rdev` is valid and non-null,
         // so it is safe to perform the FFI call.
@@ -613,6 +1021,70 @@ fn get_current_limit_regmap(&self) -> Result<i32> {
     }
 }
 
+/// Collect the non-zero voltages `list_voltage` returns for the selectors `0..n_voltages`.
+///
+/// Negative values returned by `list_voltage` are errnos and abort the listing.
+fn collect_voltages(
+    n_voltages: u32,
+    mut list_voltage: impl FnMut(u32) -> i32,
+) -> Result<KVec<i32>> {
+    let mut voltages = KVec::with_capacity(n_voltages as usize, GFP_KERNEL)?;
+    for selector in 0..n_voltages {
+        let ret = list_voltage(selector);
+        if ret < 0 {
+            return Err(Error::from_errno(ret));
+        }
+        if ret > 0 {
+            voltages.push(ret, GFP_KERNEL)?;
+        }
+    }
+    Ok(voltages)
+}
+
+/// How a requested voltage is rounded to the voltages a linear regulator can output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rounding {
+    /// Pick the closest voltage, which may be below the requested minimum.
+    Nearest,
+    /// Pick the closest voltage at or above the requested minimum, never undervolting.
+    Up,
+    /// Pick the closest voltage at or below the requested minimum.
+    Down,
+}
+
+/// Compute the selector of a linear mapping for the range `min_uv..=max_uv`.
+///
+/// `min_uv` is rounded to a multiple of `uv_step` above `base_uv` according to `rounding`, and
+/// the resulting voltage must not exceed `max_uv`.
+fn linear_voltage_sel(
+    base_uv: u32,
+    uv_step: u32,
+    linear_min_sel: u32,
+    n_voltages: u32,
+    rounding: Rounding,
+    min_uv: i32,
+    max_uv: i32,
+) -> Result<i32> {
+    if uv_step == 0 {
+        return Err(EINVAL);
+    }
+
+    let offset = i64::from(min_uv.max(base_uv as i32)) - i64::from(base_uv);
+    let (step, rem) = (offset / i64::from(uv_step), offset % i64::from(uv_step));
+    let step = match rounding {
+        Rounding::Nearest if 2 * rem >= i64::from(uv_step) => step + 1,
+        Rounding::Up if rem > 0 => step + 1,
+        _ => step,
+    };
+
+    let sel = step + i64::from(linear_min_sel);
+    let voltage = i64::from(base_uv) + step * i64::from(uv_step);
+    if sel >= i64::from(n_voltages) || voltage > i64::from(max_uv) {
+        return Err(EINVAL);
+    }
+    Ok(sel as i32)
+}
+
 /// [`Device`] type
 pub enum Type {
     /// Voltage regulator
@@ -749,6 +1221,24 @@ impl<T: Driver> Adapter<T> {
         })
     }
 
+    /// # Safety
+    ///
+    /// `rdev` must be non-null and valid.
+    unsafe extern "C" fn set_over_current_protection_callback(
+        rdev: *mut bindings::regulator_dev,
+        lim_ua: kernel::ffi::c_int,
+        severity: kernel::ffi::c_int,
+        enable: bool,
+    ) -> kernel::ffi::c_int {
+        // SAFETY: Per this function safety requirements, `rdev` is non-null and valid.
+        let mut rdev = unsafe { Device::from_raw(rdev) };
+        from_result(|| {
+            let severity = u32::try_from(severity).map_err(|_| EINVAL)?;
+            T::set_over_current_protection(&mut rdev, lim_ua, severity, enable)?;
+            Ok(0)
+        })
+    }
+
     /// # Safety
     ///
     /// `rdev` must be non-null and valid.
@@ -930,6 +1420,11 @@ impl<T: Driver> Adapter<T> {
         } else {
             None
         },
+        set_over_current_protection: if T::HAS_SET_OVER_CURRENT_PROTECTION {
+            Some(Adapter::<T>::set_over_current_protection_callback)
+        } else {
+            None
+        },
         enable: if T::HAS_ENABLE {
             Some(Adapter::<T>::enable_callback)
         } else {
@@ -989,3 +1484,173 @@ const fn build() -> &'static bindings::regulator_ops {
         &Self::VTABLE
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FORCE_PRE_DISABLE: u32 =
+        bindings::REGULATOR_EVENT_FORCE_DISABLE | bindings::REGULATOR_EVENT_PRE_DISABLE;
+    const FORCE_DISABLE: u32 =
+        bindings::REGULATOR_EVENT_FORCE_DISABLE | bindings::REGULATOR_EVENT_DISABLE;
+    const FORCE_ABORT_DISABLE: u32 =
+        bindings::REGULATOR_EVENT_FORCE_DISABLE | bindings::REGULATOR_EVENT_ABORT_DISABLE;
+
+    struct MockRdev {
+        use_count: u32,
+        events: KVec<u32>,
+        veto: bool,
+        disable_op: Option<Result>,
+        enabled: bool,
+    }
+
+    impl MockRdev {
+        fn new(use_count: u32) -> Self {
+            Self {
+                use_count,
+                events: KVec::new(),
+                veto: false,
+                disable_op: Some(Ok(())),
+                enabled: true,
+            }
+        }
+    }
+
+    impl LockedAccess for MockRdev {
+        fn use_count(&self) -> u32 {
+            self.use_count
+        }
+
+        fn notify(&mut self, event: u32) -> kernel::ffi::c_int {
+            self.events.push(event, GFP_KERNEL).unwrap();
+            if self.veto {
+                bindings::NOTIFY_STOP_MASK as _
+            } else {
+                0
+            }
+        }
+
+        fn has_disable(&self) -> bool {
+            self.disable_op.is_some()
+        }
+
+        fn disable(&mut self) -> Result {
+            let res = self.disable_op.unwrap();
+            if res.is_ok() {
+                self.enabled = false;
+            }
+            res
+        }
+    }
+
+    #[test]
+    fn test_force_disable_keeps_enable_count() {
+        let mut rdev = MockRdev::new(3);
+
+        assert_eq!(force_disable_locked(&mut rdev), Ok(()));
+        assert!(!rdev.enabled);
+        assert_eq!(rdev.events[..], [FORCE_PRE_DISABLE, FORCE_DISABLE]);
+        assert_eq!(rdev.use_count(), 3);
+    }
+
+    #[test]
+    fn test_force_disable_errors() {
+        let mut rdev = MockRdev::new(1);
+        rdev.disable_op = None;
+        assert_eq!(force_disable_locked(&mut rdev), Err(ENOTSUPP));
+        assert!(rdev.events.is_empty());
+
+        let mut rdev = MockRdev::new(1);
+        rdev.veto = true;
+        assert_eq!(force_disable_locked(&mut rdev), Err(EINVAL));
+        assert!(rdev.enabled);
+        assert_eq!(rdev.events[..], [FORCE_PRE_DISABLE]);
+
+        let mut rdev = MockRdev::new(1);
+        rdev.disable_op = Some(Err(EIO));
+        assert_eq!(force_disable_locked(&mut rdev), Err(EIO));
+        assert!(rdev.enabled);
+        assert_eq!(rdev.events[..], [FORCE_PRE_DISABLE, FORCE_ABORT_DISABLE]);
+    }
+
+    #[test]
+    fn test_linear_voltage_sel() {
+        let sel = |rounding, min_uv, max_uv| {
+            linear_voltage_sel(600_000, 6250, 0, 128, rounding, min_uv, max_uv)
+        };
+
+        // 603_000 lies between 600_000 (0) and 606_250 (1), closer to 600_000.
+        assert_eq!(sel(Rounding::Nearest, 603_000, 700_000), Ok(0));
+        assert_eq!(sel(Rounding::Up, 603_000, 700_000), Ok(1));
+        assert_eq!(sel(Rounding::Down, 603_000, 700_000), Ok(0));
+
+        // 604_000 is closer to 606_250.
+        assert_eq!(sel(Rounding::Nearest, 604_000, 700_000), Ok(1));
+        assert_eq!(sel(Rounding::Up, 604_000, 700_000), Ok(1));
+        assert_eq!(sel(Rounding::Down, 604_000, 700_000), Ok(0));
+
+        // Aligned voltages are the same for every mode.
+        assert_eq!(sel(Rounding::Nearest, 606_250, 606_250), Ok(1));
+        assert_eq!(sel(Rounding::Up, 606_250, 606_250), Ok(1));
+        assert_eq!(sel(Rounding::Down, 606_250, 606_250), Ok(1));
+
+        // Requests below the first voltage are clamped.
+        assert_eq!(sel(Rounding::Down, 500_000, 700_000), Ok(0));
+
+        assert_eq!(sel(Rounding::Up, 603_000, 603_000), Err(EINVAL));
+        assert_eq!(sel(Rounding::Up, 1_500_000, 1_500_000), Err(EINVAL));
+        assert_eq!(
+            linear_voltage_sel(600_000, 0, 0, 128, Rounding::Up, 603_000, 700_000),
+            Err(EINVAL)
+        );
+        assert_eq!(
+            linear_voltage_sel(600_000, 6250, 2, 130, Rounding::Up, 603_000, 700_000),
+            Ok(3)
+        );
+    }
+
+    #[test]
+    fn test_collect_voltages() {
+        // Linear mapping from 600mV in 100mV steps, starting at selector 2, like
+        // `regulator_list_voltage_linear`.
+        let linear = |selector: u32| match selector {
+            0 | 1 => 0,
+            _ => 600_000 + 100_000 * (selector as i32 - 2),
+        };
+
+        let voltages = collect_voltages(6, linear).unwrap();
+        assert_eq!(&voltages[..], [600_000, 700_000, 800_000, 900_000]);
+
+        assert!(collect_voltages(0, linear).unwrap().is_empty());
+
+        let failing = |selector| match selector {
+            3 => EINVAL.to_errno(),
+            _ => linear(selector),
+        };
+        assert_eq!(collect_voltages(6, failing).err(), Some(EINVAL));
+    }
+
+    #[cfg(CONFIG_REGMAP)]
+    #[test]
+    fn test_ramp_delay_us() {
+        // 600mV to 700mV at 10mV/us.
+        assert_eq!(ramp_delay_us(600_000, 700_000, 10_000), 10);
+        assert_eq!(ramp_delay_us(700_000, 600_000, 10_000), 10);
+        // A partial microsecond is rounded up.
+        assert_eq!(ramp_delay_us(600_000, 606_250, 10_000), 1);
+        assert_eq!(ramp_delay_us(600_000, 1_393_750, 6_250), 127);
+        assert_eq!(ramp_delay_us(600_000, 600_000, 10_000), 0);
+        // No ramp rate, no delay.
+        assert_eq!(ramp_delay_us(600_000, 700_000, 0), 0);
+    }
+
+    #[cfg(CONFIG_REGMAP)]
+    #[test]
+    fn test_check_voltage_sel() {
+        assert_eq!(check_voltage_sel(0, 4), Ok(0));
+        assert_eq!(check_voltage_sel(3, 4), Ok(3));
+        assert_eq!(check_voltage_sel(4, 4), Err(ERANGE));
+        assert_eq!(check_voltage_sel(0x7f, 4), Err(ERANGE));
+        assert_eq!(check_voltage_sel(0x7f, 0), Ok(0x7f));
+    }
+}
@@ -0,0 +1,46 @@
+// SPDX-License-Identifier: GPL-2.0
+
+//! Abstractions for the SPI bus.
+//!
+//! C header: [`include/linux/spi/spi.h`](srctree/include/linux/spi/spi.h)
+//!
+//! Only what is needed to hand an SPI device to other abstractions, e.g.
+//! [`Regmap::init_spi`](crate::regmap::Regmap::init_spi), is provided so far. There is no SPI
+//! driver abstraction yet.
+
+use crate::{bindings, container_of, device, types::ARef};
+
+/// An SPI device.
+///
+/// # Invariants
+///
+/// `Device` holds a valid reference of `ARef<device::Device>` whose underlying `struct device` is
+/// a member of a `struct spi_device`.
+#[derive(Clone)]
+pub struct Device(ARef<device::Device>);
+
+impl Device {
+    /// Convert a raw kernel device into an SPI `Device`.
+    ///
+    /// # Safety
+    ///
+    /// `dev` must be an `ARef<device::Device>` whose underlying `bindings::device` is a member of
+    /// a `bindings::spi_device`.
+    pub unsafe fn from_dev(dev: ARef<device::Device>) -> Self {
+        // INVARIANT: Guaranteed by the safety requirements.
+        Self(dev)
+    }
+
+    /// Returns the raw `struct spi_device`.
+    pub fn as_raw(&self) -> *mut bindings::spi_device {
+        // SAFETY: By the type invariant `self.0.as_raw` is a pointer to the `struct device`
+        // embedded in `struct spi_device`.
+        unsafe { container_of!(self.0.as_raw(), bindings::spi_device, dev) }.cast_mut()
+    }
+}
+
+impl AsRef<device::Device> for Device {
+    fn as_ref(&self) -> &device::Device {
+        &self.0
+    }
+}
@@ -11,6 +11,9 @@
 /// The number of nanoseconds per millisecond.
 pub const NSEC_PER_MSEC: i64 = bindings::NSEC_PER_MSEC as i64;
 
+/// The number of nanoseconds per microsecond.
+pub const NSEC_PER_USEC: i64 = bindings::NSEC_PER_USEC as i64;
+
 /// The time unit of Linux kernel. One jiffy equals (1/HZ) second.
 pub type Jiffies = crate::ffi::c_ulong;
 
@@ -58,6 +61,12 @@ pub fn to_ns(self) -> i64 {
         self.inner
     }
 
+    /// Returns the number of microseconds.
+    #[inline]
+    pub fn to_us(self) -> i64 {
+        self.divns_constant::<NSEC_PER_USEC>()
+    }
+
     /// Returns the number of milliseconds.
     #[inline]
     pub fn to_ms(self) -> i64 {
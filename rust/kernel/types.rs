@@ -528,6 +528,43 @@ impl Integer for $typ {
     i8, i16, i32, i64, isize,
 }
 
+/// A 16-bit value that a device splits across a high and a low 8-bit register.
+///
+/// # Examples
+///
+/// ```
+/// use kernel::types::RegPair;
+///
+/// let pair = RegPair::from_u16(0x1234);
+/// assert_eq!((pair.hi, pair.lo), (0x12, 0x34));
+/// assert_eq!(RegPair::new(0xab, 0xcd).to_u16(), 0xabcd);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RegPair {
+    /// Most significant byte.
+    pub hi: u8,
+    /// Least significant byte.
+    pub lo: u8,
+}
+
+impl RegPair {
+    /// Creates a pair from the contents of the high and the low register.
+    pub const fn new(hi: u8, lo: u8) -> Self {
+        Self { hi, lo }
+    }
+
+    /// Splits `value` into the bytes for the high and the low register.
+    pub const fn from_u16(value: u16) -> Self {
+        let [hi, lo] = value.to_be_bytes();
+        Self { hi, lo }
+    }
+
+    /// Combines the high and the low register into one value.
+    pub const fn to_u16(self) -> u16 {
+        u16::from_be_bytes([self.hi, self.lo])
+    }
+}
+
 /// Zero-sized type to mark types not [`Send`].
 ///
 /// Add this type as a field to your struct if your type should not be sent to a different task.
@@ -548,3 +585,28 @@ impl Integer for $typ {
 /// [`NotThreadSafe`]: type@NotThreadSafe
 #[allow(non_upper_case_globals)]
 pub const NotThreadSafe: NotThreadSafe = PhantomData;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reg_pair_round_trip() {
+        for value in 0..=u16::MAX {
+            assert_eq!(RegPair::from_u16(value).to_u16(), value);
+        }
+        for hi in 0..=u8::MAX {
+            for lo in 0..=u8::MAX {
+                let pair = RegPair::new(hi, lo);
+                assert_eq!(RegPair::from_u16(pair.to_u16()), pair);
+            }
+        }
+    }
+
+    #[test]
+    fn test_reg_pair_byte_order() {
+        assert_eq!(RegPair::from_u16(0x00ff), RegPair::new(0x00, 0xff));
+        assert_eq!(RegPair::from_u16(0xff00), RegPair::new(0xff, 0x00));
+        assert_eq!(RegPair::new(0x01, 0x02).to_u16(), 0x0102);
+    }
+}